@@ -1,16 +1,29 @@
 use std::{
+    cell::{Cell, RefCell},
     collections::HashMap,
+    path::Path,
+    rc::Rc,
     sync::{Arc, Mutex},
+    thread,
     time::{Duration, Instant},
 };
 
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
 use eframe::egui;
 
 use crate::{
-    audio::AudioRecorder,
-    config::{AppConfig, Provider},
+    audio::{self, AudioRecorder},
+    config::{
+        self, AppConfig, BindingAction, DownmixMode, HotkeyBinding, HotkeyMode, InjectPlacement,
+        ListenDuck, LogLevel, Provider, ReplacementRule, SttBackend, SttCoalesce, Theme,
+        UploadFormat, VoiceAlias,
+    },
+    diagnostics,
+    hotkey::{self, HotkeyControl},
+    inject, logging,
     server::ServerControl,
+    stt::SttJob,
+    transcript,
     tts::{SpeakRequest, TtsRequest},
 };
 
@@ -20,44 +33,172 @@ pub enum AppEvent {
     Warning(String),
     Error(String),
     Listening(bool),
+    CaptureLatched(bool),
     SttBusy(bool),
     TtsBusy(bool),
     LastTranscript(String),
+    // Stage timing for latency tuning; logged as an info line.
+    Metric {
+        stage: &'static str,
+        ms: u64,
+    },
+    HotkeyListenerFailed(String),
+    SttTestResult {
+        provider: &'static str,
+        seconds: f32,
+        outcome: Result<String, String>,
+    },
     LastSpoken(String),
     ServerOnline(String),
     ServerOffline,
+    ConfigChanged,
+}
+
+// Capture/busy state mirrored from events so non-UI threads (the HTTP server,
+// hotkeys) can read it.
+#[derive(Debug, Clone, Default)]
+pub struct Status {
+    pub listening: bool,
+    pub stt_busy: bool,
+    pub tts_busy: bool,
+    pub last_transcript: String,
+}
+
+impl Status {
+    pub fn apply(&mut self, event: &AppEvent) {
+        match event {
+            AppEvent::Listening(v) => self.listening = *v,
+            AppEvent::SttBusy(v) => self.stt_busy = *v,
+            AppEvent::TtsBusy(v) => self.tts_busy = *v,
+            AppEvent::LastTranscript(text) => self.last_transcript = text.clone(),
+            _ => {}
+        }
+    }
 }
 
 pub struct Push2TypeApp {
     config: Arc<Mutex<AppConfig>>,
     events: Receiver<AppEvent>,
     tts_tx: Sender<TtsRequest>,
-    stt_tx: Sender<Vec<i16>>,
+    stt_tx: Sender<SttJob>,
     server_control: ServerControl,
+    hotkey_control: HotkeyControl,
+    settings_filter: String,
+    search: Rc<SettingsSearch>,
+    // "Hold to Talk" button state as last sent to the hotkey worker.
+    push_held: bool,
     recorder: Arc<AudioRecorder>,
     logs: Vec<String>,
     listening: bool,
+    latched: bool,
     stt_busy: bool,
     tts_busy: bool,
+    // "Monitor Mic" is a setup aid, so it always starts off and isn't saved.
+    monitoring: bool,
+    monitor_on_speakers: bool,
     last_transcript: String,
+    last_metrics: HashMap<&'static str, u64>,
+    hotkey_error: Option<String>,
+    stt_test_output: String,
+    stt_test_summary: String,
+    diagnostics: Vec<diagnostics::Step>,
+    diagnostics_rx: Option<Receiver<diagnostics::Step>>,
     last_spoken: String,
     endpoint: String,
     persona_input: String,
     message_input: String,
     hotkey_draft: String,
+    cancel_hotkey_draft: String,
+    hotkey_mode_draft: HotkeyMode,
+    double_tap_ms_draft: u64,
+    min_capture_ms_draft: u64,
+    capture_cooldown_ms_draft: u64,
+    release_grace_ms_draft: u64,
+    cue_sounds_draft: bool,
     server_port_draft: u16,
     tts_bridge_enabled_draft: bool,
+    inject_enabled_draft: bool,
     show_endpoint_text_draft: bool,
+    server_auth_token_draft: String,
+    speak_rate_per_min_draft: u32,
+    log_to_file_draft: bool,
+    mock_mode_draft: bool,
+    log_level_draft: LogLevel,
+    log_max_lines_draft: usize,
+    theme_draft: Theme,
+    listen_accent_draft: [u8; 3],
+    always_on_top_draft: bool,
+    auto_resize_draft: bool,
+    // Saved position to move to once the first frame says whether it's on screen.
+    restore_position: Option<(f32, f32)>,
+    // Window position/size last written to config, and a change waiting to settle.
+    saved_geometry: Option<WindowGeometry>,
+    pending_geometry: Option<(WindowGeometry, Instant)>,
+    applied_theme: Option<Theme>,
+    applied_always_on_top: Option<bool>,
+    input_gain_draft: f32,
+    monitor_volume_draft: f32,
+    downmix_mode_draft: DownmixMode,
+    preroll_ms_draft: u64,
+    noise_gate_enabled_draft: bool,
+    tts_normalize_draft: bool,
+    tts_target_peak_draft: f32,
+    tts_keep_output_open_draft: bool,
+    duck_tts_on_listen_draft: ListenDuck,
+    noise_gate_threshold_draft: u16,
+    vad_enabled_draft: bool,
+    vad_silence_ms_draft: u64,
+    vad_threshold_draft: u16,
     stt_language_draft: String,
+    stt_prompt_draft: String,
+    stt_min_confidence_draft: f32,
+    stt_streaming_draft: bool,
+    stt_upload_format_draft: UploadFormat,
+    stt_coalesce_draft: SttCoalesce,
+    replacements_draft: Vec<ReplacementRule>,
+    voice_aliases_draft: Vec<(String, VoiceAlias)>,
+    strip_prefixes_draft: String,
+    hotkey_bindings_draft: Vec<HotkeyBinding>,
+    inject_suffix_draft: String,
+    inject_command_draft: String,
+    inject_settle_ms_draft: u64,
+    inject_focus_stable_ms_draft: u64,
+    inject_focus_timeout_ms_draft: u64,
+    http_timeout_secs_draft: u64,
+    inject_verify_draft: bool,
+    inject_submit_draft: bool,
+    inject_placement_draft: InjectPlacement,
+    auto_capitalize_draft: bool,
+    auto_period_draft: bool,
+    speak_transcript_back_draft: bool,
+    paste_shortcut_draft: String,
+    inject_allowlist_draft: String,
     stt_model_draft: String,
     stt_model_by_provider_draft: HashMap<String, String>,
+    stt_language_by_provider_draft: HashMap<String, String>,
+    stt_base_urls_draft: HashMap<String, String>,
+    tts_base_urls_draft: HashMap<String, String>,
     stt_provider_draft: Provider,
+    local_whisper_draft: bool,
+    local_whisper_model_draft: String,
+    save_recordings_dir_draft: String,
     tts_provider_draft: Provider,
     tts_voice_draft: String,
     tts_voice_by_provider_draft: HashMap<String, String>,
     xai_style_draft: String,
+    tts_verbatim_draft: bool,
+    xai_realtime_model_draft: String,
+    openai_tts_model_draft: String,
+    groq_tts_model_draft: String,
+    elevenlabs_tts_model_draft: String,
+    settings_path_input: String,
+    reset_armed_at: Option<Instant>,
     last_save_status: Option<(String, Instant)>,
     last_applied_height: f32,
+    previous_applied_height: f32,
+    last_resize_at: Instant,
+    #[cfg(windows)]
+    tray: Option<crate::tray::Tray>,
 }
 
 impl Push2TypeApp {
@@ -65,32 +206,37 @@ impl Push2TypeApp {
         config: Arc<Mutex<AppConfig>>,
         events: Receiver<AppEvent>,
         tts_tx: Sender<TtsRequest>,
-        stt_tx: Sender<Vec<i16>>,
+        stt_tx: Sender<SttJob>,
         recorder: Arc<AudioRecorder>,
         server_control: ServerControl,
+        hotkey_control: HotkeyControl,
     ) -> Self {
-        let cfg = config.lock().expect("config lock").clone();
-        let initial_stt_model = cfg.stt_model_for(&cfg.stt_provider);
-        let mut tts_voice_by_provider_draft = HashMap::new();
-        tts_voice_by_provider_draft.insert("xai".to_string(), cfg.xai_voice.clone());
-        tts_voice_by_provider_draft.insert("openai".to_string(), cfg.openai_voice.clone());
-        tts_voice_by_provider_draft.insert("groq".to_string(), cfg.groq_voice.clone());
-        let tts_voice_draft = tts_voice_by_provider_draft
-            .get(provider_label(cfg.tts_provider))
-            .cloned()
-            .unwrap_or_else(|| cfg.xai_voice.clone());
-        Self {
+        let cfg = config::lock(&config).clone();
+        let mut app = Self {
             config,
             events,
             tts_tx,
             stt_tx,
             server_control,
+            hotkey_control,
+            push_held: false,
+            settings_filter: String::new(),
+            search: Rc::default(),
             recorder,
             logs: vec!["Push2Type Rust satellite started.".to_string()],
             listening: false,
+            latched: false,
             stt_busy: false,
             tts_busy: false,
+            monitoring: false,
+            monitor_on_speakers: false,
             last_transcript: String::new(),
+            last_metrics: HashMap::new(),
+            hotkey_error: None,
+            stt_test_output: String::new(),
+            stt_test_summary: String::new(),
+            diagnostics: Vec::new(),
+            diagnostics_rx: None,
             last_spoken: String::new(),
             endpoint: if cfg.tts_bridge_enabled {
                 format!("http://127.0.0.1:{}/speak", cfg.server_port)
@@ -99,49 +245,1525 @@ impl Push2TypeApp {
             },
             persona_input: "codex".to_string(),
             message_input: "The quick brown fox jumped over the lazy dog.".to_string(),
-            hotkey_draft: cfg.hotkey,
-            server_port_draft: cfg.server_port,
-            tts_bridge_enabled_draft: cfg.tts_bridge_enabled,
-            show_endpoint_text_draft: cfg.show_endpoint_text,
-            stt_language_draft: cfg.stt_language,
-            stt_model_draft: initial_stt_model,
-            stt_model_by_provider_draft: cfg.stt_model_by_provider,
+            hotkey_draft: String::new(),
+            cancel_hotkey_draft: String::new(),
+            hotkey_mode_draft: HotkeyMode::default(),
+            double_tap_ms_draft: 0,
+            min_capture_ms_draft: 0,
+            capture_cooldown_ms_draft: 0,
+            release_grace_ms_draft: 0,
+            cue_sounds_draft: false,
+            server_port_draft: 0,
+            tts_bridge_enabled_draft: false,
+            inject_enabled_draft: true,
+            show_endpoint_text_draft: false,
+            server_auth_token_draft: String::new(),
+            speak_rate_per_min_draft: 0,
+            log_to_file_draft: false,
+            mock_mode_draft: false,
+            log_level_draft: LogLevel::Info,
+            log_max_lines_draft: 300,
+            theme_draft: Theme::System,
+            listen_accent_draft: [229, 57, 53],
+            always_on_top_draft: true,
+            auto_resize_draft: true,
+            restore_position: cfg.window_x.zip(cfg.window_y),
+            saved_geometry: None,
+            pending_geometry: None,
+            applied_theme: None,
+            applied_always_on_top: None,
+            input_gain_draft: 1.0,
+            monitor_volume_draft: 0.3,
+            downmix_mode_draft: DownmixMode::Average,
+            preroll_ms_draft: 0,
+            noise_gate_enabled_draft: false,
+            tts_normalize_draft: false,
+            tts_target_peak_draft: 0.0,
+            tts_keep_output_open_draft: false,
+            duck_tts_on_listen_draft: ListenDuck::Off,
+            noise_gate_threshold_draft: 0,
+            vad_enabled_draft: false,
+            vad_silence_ms_draft: 0,
+            vad_threshold_draft: 0,
+            stt_language_draft: String::new(),
+            stt_prompt_draft: String::new(),
+            stt_min_confidence_draft: 0.0,
+            stt_streaming_draft: false,
+            stt_upload_format_draft: UploadFormat::Wav,
+            stt_coalesce_draft: SttCoalesce::Fifo,
+            replacements_draft: Vec::new(),
+            voice_aliases_draft: Vec::new(),
+            strip_prefixes_draft: String::new(),
+            hotkey_bindings_draft: Vec::new(),
+            inject_suffix_draft: String::new(),
+            inject_command_draft: String::new(),
+            inject_settle_ms_draft: 0,
+            inject_focus_stable_ms_draft: 0,
+            inject_focus_timeout_ms_draft: 0,
+            http_timeout_secs_draft: 0,
+            inject_verify_draft: true,
+            inject_submit_draft: false,
+            inject_placement_draft: InjectPlacement::Cursor,
+            auto_capitalize_draft: false,
+            auto_period_draft: false,
+            speak_transcript_back_draft: false,
+            paste_shortcut_draft: String::new(),
+            inject_allowlist_draft: String::new(),
+            stt_model_draft: String::new(),
+            stt_model_by_provider_draft: HashMap::new(),
+            stt_language_by_provider_draft: HashMap::new(),
+            stt_base_urls_draft: HashMap::new(),
+            tts_base_urls_draft: HashMap::new(),
             stt_provider_draft: cfg.stt_provider,
+            local_whisper_draft: false,
+            local_whisper_model_draft: String::new(),
+            save_recordings_dir_draft: String::new(),
             tts_provider_draft: cfg.tts_provider,
-            tts_voice_draft,
-            tts_voice_by_provider_draft,
-            xai_style_draft: cfg.xai_tts_style,
+            tts_voice_draft: String::new(),
+            tts_voice_by_provider_draft: HashMap::new(),
+            xai_style_draft: String::new(),
+            tts_verbatim_draft: true,
+            xai_realtime_model_draft: String::new(),
+            openai_tts_model_draft: String::new(),
+            groq_tts_model_draft: String::new(),
+            elevenlabs_tts_model_draft: String::new(),
+            settings_path_input: default_settings_export_path(),
+            reset_armed_at: None,
             last_save_status: None,
             last_applied_height: 280.0,
+            previous_applied_height: 280.0,
+            last_resize_at: Instant::now(),
+            #[cfg(windows)]
+            tray: None,
+        };
+        app.load_drafts(&cfg);
+        app
+    }
+
+    // Resets every `_draft` field from `cfg`; used at startup and whenever config is replaced.
+    fn load_drafts(&mut self, cfg: &AppConfig) {
+        self.hotkey_draft = cfg.hotkey.clone();
+        self.cancel_hotkey_draft = cfg.cancel_hotkey.clone();
+        self.hotkey_mode_draft = cfg.hotkey_mode;
+        self.double_tap_ms_draft = cfg.double_tap_ms;
+        self.min_capture_ms_draft = cfg.min_capture_ms;
+        self.capture_cooldown_ms_draft = cfg.capture_cooldown_ms;
+        self.release_grace_ms_draft = cfg.release_grace_ms;
+        self.cue_sounds_draft = cfg.cue_sounds;
+        self.server_port_draft = cfg.server_port;
+        self.tts_bridge_enabled_draft = cfg.tts_bridge_enabled;
+        self.inject_enabled_draft = cfg.inject_enabled;
+        self.show_endpoint_text_draft = cfg.show_endpoint_text;
+        self.server_auth_token_draft = cfg.server_auth_token.clone();
+        self.speak_rate_per_min_draft = cfg.speak_rate_per_min;
+        self.log_to_file_draft = cfg.log_to_file;
+        self.mock_mode_draft = cfg.mock_mode;
+        self.log_level_draft = cfg.log_level;
+        self.log_max_lines_draft = cfg.log_max_lines;
+        self.theme_draft = cfg.theme;
+        self.listen_accent_draft = cfg.listen_accent;
+        self.always_on_top_draft = cfg.always_on_top;
+        self.auto_resize_draft = cfg.auto_resize;
+        // Gain is applied live from the slider, so keep the recorder in step with the draft.
+        self.input_gain_draft = cfg.input_gain;
+        self.recorder.set_input_gain(cfg.input_gain);
+        self.monitor_volume_draft = cfg.monitor_volume;
+        self.recorder.set_monitor_volume(cfg.monitor_volume);
+        self.downmix_mode_draft = cfg.downmix_mode;
+        self.recorder.set_downmix(cfg.downmix_mode);
+        self.preroll_ms_draft = cfg.preroll_ms;
+        self.recorder.set_preroll(cfg.preroll_ms);
+        self.recorder.set_capture_reserve(cfg.capture_reserve_secs);
+        self.noise_gate_enabled_draft = cfg.noise_gate_enabled;
+        self.tts_normalize_draft = cfg.tts_normalize;
+        self.tts_target_peak_draft = cfg.tts_target_peak;
+        self.tts_keep_output_open_draft = cfg.tts_keep_output_open;
+        self.duck_tts_on_listen_draft = cfg.duck_tts_on_listen;
+        self.noise_gate_threshold_draft = cfg.noise_gate_threshold;
+        self.vad_enabled_draft = cfg.vad_enabled;
+        self.vad_silence_ms_draft = cfg.vad_silence_ms;
+        self.vad_threshold_draft = cfg.vad_threshold;
+        self.stt_language_draft = cfg.stt_language_for(&cfg.stt_provider);
+        self.stt_prompt_draft = cfg.stt_prompt.clone();
+        self.stt_min_confidence_draft = cfg.stt_min_confidence;
+        self.stt_streaming_draft = cfg.stt_streaming;
+        self.stt_upload_format_draft = cfg.stt_upload_format;
+        self.stt_coalesce_draft = cfg.stt_coalesce;
+        self.replacements_draft = cfg.replacements.clone();
+        self.voice_aliases_draft = cfg
+            .voice_aliases
+            .iter()
+            .map(|(name, alias)| (name.clone(), alias.clone()))
+            .collect();
+        self.voice_aliases_draft.sort_by(|a, b| a.0.cmp(&b.0));
+        self.strip_prefixes_draft = cfg.strip_prefixes.join("\n");
+        self.hotkey_bindings_draft = cfg.hotkey_bindings.clone();
+        self.inject_suffix_draft = cfg.inject_suffix.clone();
+        self.inject_command_draft = cfg.inject_command.clone().unwrap_or_default();
+        self.inject_settle_ms_draft = cfg.inject_settle_ms;
+        self.inject_focus_stable_ms_draft = cfg.inject_focus_stable_ms;
+        self.inject_focus_timeout_ms_draft = cfg.inject_focus_timeout_ms;
+        self.http_timeout_secs_draft = cfg.http_timeout_secs;
+        self.inject_verify_draft = cfg.inject_verify;
+        self.inject_submit_draft = cfg.inject_submit;
+        self.inject_placement_draft = cfg.inject_placement;
+        self.auto_capitalize_draft = cfg.auto_capitalize;
+        self.auto_period_draft = cfg.auto_period;
+        self.speak_transcript_back_draft = cfg.speak_transcript_back;
+        self.paste_shortcut_draft = cfg.paste_shortcut.clone();
+        self.inject_allowlist_draft = cfg.inject_allowlist.join("\n");
+        self.stt_model_draft = cfg.stt_model_for(&cfg.stt_provider);
+        self.stt_model_by_provider_draft = cfg.stt_model_by_provider.clone();
+        self.stt_language_by_provider_draft = cfg.stt_language_by_provider.clone();
+        self.stt_base_urls_draft = cfg.stt_base_urls.clone();
+        self.tts_base_urls_draft = cfg.tts_base_urls.clone();
+        self.stt_provider_draft = cfg.stt_provider;
+        self.local_whisper_draft = cfg.stt_backend == SttBackend::Local;
+        self.local_whisper_model_draft = cfg.local_whisper_model.clone();
+        self.save_recordings_dir_draft = cfg.save_recordings_dir.clone().unwrap_or_default();
+        self.tts_provider_draft = cfg.tts_provider;
+        self.tts_voice_by_provider_draft = HashMap::new();
+        self.tts_voice_by_provider_draft
+            .insert("xai".to_string(), cfg.xai_voice.clone());
+        self.tts_voice_by_provider_draft
+            .insert("openai".to_string(), cfg.openai_voice.clone());
+        self.tts_voice_by_provider_draft
+            .insert("groq".to_string(), cfg.groq_voice.clone());
+        self.tts_voice_by_provider_draft
+            .insert("elevenlabs".to_string(), cfg.elevenlabs_voice.clone());
+        self.tts_voice_draft = self
+            .tts_voice_by_provider_draft
+            .get(provider_label(cfg.tts_provider))
+            .cloned()
+            .unwrap_or_else(|| cfg.xai_voice.clone());
+        self.xai_style_draft = cfg.xai_tts_style.clone();
+        self.tts_verbatim_draft = cfg.tts_verbatim;
+        self.xai_realtime_model_draft = cfg.xai_realtime_model.clone();
+        self.openai_tts_model_draft = cfg.openai_tts_model.clone();
+        self.groq_tts_model_draft = cfg.groq_tts_model.clone();
+        self.elevenlabs_tts_model_draft = cfg.elevenlabs_tts_model.clone();
+    }
+
+    // Swaps in a whole new config (import/reset), persists it, and re-syncs runtime state.
+    fn replace_config(&mut self, next: AppConfig) -> anyhow::Result<()> {
+        next.save()?;
+        *config::lock(&self.config) = next.clone();
+        self.load_drafts(&next);
+        self.hotkey_control.reload();
+        self.apply_server_settings();
+        Ok(())
+    }
+
+    #[cfg(windows)]
+    pub fn with_tray(mut self, tray: anyhow::Result<crate::tray::Tray>) -> Self {
+        match tray {
+            Ok(tray) => self.tray = Some(tray),
+            Err(e) => self.push_log(LogLevel::Warn, format!("WARN: tray icon unavailable: {e}")),
+        }
+        self
+    }
+
+    // Pushes the drafted port and enable flag to the running server without touching the
+    // config file; Save Configuration does this too, and also persists them.
+    fn apply_server_settings(&mut self) {
+        self.server_control.set_port(self.server_port_draft);
+        self.server_control
+            .set_enabled(self.tts_bridge_enabled_draft);
+        if !self.tts_bridge_enabled_draft {
+            self.endpoint = "Disabled".to_string();
+        }
+    }
+
+    // Quick toggle outside the config form: applies and persists immediately.
+    fn set_bridge_enabled(&mut self, enabled: bool) {
+        self.tts_bridge_enabled_draft = enabled;
+        let save_res = {
+            let mut cfg = config::lock(&self.config);
+            cfg.tts_bridge_enabled = enabled;
+            cfg.save()
+        };
+        if let Err(e) = save_res {
+            self.last_save_status = Some((format!("Save failed: {e}"), Instant::now()));
         }
+        self.apply_server_settings();
+    }
+
+    // Same as the bridge toggle: flipped often while testing, so it saves right away.
+    fn set_inject_enabled(&mut self, enabled: bool) {
+        self.inject_enabled_draft = enabled;
+        let save_res = {
+            let mut cfg = config::lock(&self.config);
+            cfg.inject_enabled = enabled;
+            cfg.save()
+        };
+        if let Err(e) = save_res {
+            self.last_save_status = Some((format!("Save failed: {e}"), Instant::now()));
+        }
+    }
+
+    // The window opens at the default placement and moves to the saved spot only if
+    // that is on a monitor; one unplugged since the last run would leave it off screen.
+    fn restore_window_position(&mut self, ctx: &egui::Context) {
+        let Some((x, y)) = self.restore_position else {
+            return;
+        };
+        let Some(visible) = ctx.input(|i| crate::position_visible(x, y, i.viewport())) else {
+            return;
+        };
+        self.restore_position = None;
+        if visible {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(x, y)));
+        }
+    }
+
+    // Writes the window's position and size to config once it has stopped changing
+    // for a moment, so a drag doesn't save on every frame.
+    fn track_window_geometry(&mut self, ctx: &egui::Context) {
+        let auto_resize = self.auto_resize_draft;
+        let Some(current) = ctx.input(|i| {
+            let viewport = i.viewport();
+            let outer = viewport.outer_rect?;
+            let inner = viewport.inner_rect?;
+            // Content drives the height while auto-resize is on; don't save every change.
+            let height = (!auto_resize).then_some(inner.height());
+            Some((outer.min.x, outer.min.y, inner.width(), height))
+        }) else {
+            return;
+        };
+        let Some(saved) = self.saved_geometry else {
+            // Where the window opened is what config already says.
+            self.saved_geometry = Some(current);
+            return;
+        };
+        match self.pending_geometry {
+            Some((pending, since)) if pending == current => {
+                if since.elapsed() >= GEOMETRY_SAVE_DELAY {
+                    self.save_window_geometry(current);
+                }
+            }
+            _ if current != saved => self.pending_geometry = Some((current, Instant::now())),
+            _ => self.pending_geometry = None,
+        }
+    }
+
+    fn save_window_geometry(&mut self, (x, y, width, height): WindowGeometry) {
+        self.saved_geometry = Some((x, y, width, height));
+        self.pending_geometry = None;
+        let save_res = {
+            let mut cfg = config::lock(&self.config);
+            cfg.window_x = Some(x);
+            cfg.window_y = Some(y);
+            cfg.window_w = Some(width);
+            if height.is_some() {
+                cfg.window_h = height;
+            }
+            cfg.save()
+        };
+        if let Err(e) = save_res {
+            self.last_save_status = Some((format!("Save failed: {e}"), Instant::now()));
+        }
+    }
+
+    // Changed from the log panel itself, so like the toggles above it saves right away.
+    fn save_log_view(&mut self) {
+        let save_res = {
+            let mut cfg = config::lock(&self.config);
+            cfg.log_level = self.log_level_draft;
+            cfg.log_max_lines = self.log_max_lines_draft;
+            cfg.save()
+        };
+        if let Err(e) = save_res {
+            self.last_save_status = Some((format!("Save failed: {e}"), Instant::now()));
+        }
+    }
+
+    // "last STT: 820ms / last TTS: 1.2s synth, 3.4s playback", once anything has run.
+    fn timing_summary(&self) -> Option<String> {
+        let show = |ms: u64| {
+            if ms < 1000 {
+                format!("{ms}ms")
+            } else {
+                format!("{:.1}s", ms as f32 / 1000.0)
+            }
+        };
+        let mut parts = Vec::new();
+        if let Some(&ms) = self.last_metrics.get("stt") {
+            parts.push(format!("last STT: {}", show(ms)));
+        }
+        let tts: Vec<String> = [("tts synthesis", "synth"), ("tts playback", "playback")]
+            .iter()
+            .filter_map(|(stage, label)| {
+                self.last_metrics
+                    .get(stage)
+                    .map(|&ms| format!("{} {label}", show(ms)))
+            })
+            .collect();
+        if !tts.is_empty() {
+            parts.push(format!("last TTS: {}", tts.join(", ")));
+        }
+        (!parts.is_empty()).then(|| parts.join(" / "))
+    }
+
+    fn set_monitoring(&mut self, on: bool) {
+        self.monitoring = on;
+        if !on {
+            self.recorder.stop_monitor();
+            return;
+        }
+        let output = audio::default_output_name();
+        self.monitor_on_speakers = !output.as_deref().is_some_and(audio::looks_like_headphones);
+        if self.monitor_on_speakers {
+            self.push_log(
+                LogLevel::Warn,
+                format!(
+                    "WARN: monitoring mic on {}; use headphones to avoid feedback",
+                    output.as_deref().unwrap_or("the default output")
+                ),
+            );
+        }
+        self.recorder.pause_monitor(self.tts_busy);
+        self.recorder.start_monitor();
+    }
+
+    fn push_log(&mut self, level: LogLevel, line: String) {
+        if level == LogLevel::Error || level <= self.log_level_draft {
+            self.logs.push(line);
+        }
+    }
+
+    // Speaks with the voice settings currently shown in the TTS section, saved or not.
+    fn speak_test(&self, message: String) {
+        let req = TtsRequest {
+            speak: SpeakRequest {
+                message,
+                persona: Some(self.persona_input.clone()),
+                voice: Some(self.tts_voice_draft.clone()),
+                provider: Some(self.tts_provider_draft),
+                show_text: Some(true),
+                style: Some(self.xai_style_draft.clone()),
+            },
+            progress: None,
+        };
+        let _ = self.tts_tx.send(req);
+    }
+
+    fn export_settings(&self) -> anyhow::Result<()> {
+        let cfg = config::lock(&self.config).clone();
+        cfg.export_to(Path::new(self.settings_path_input.trim()))
+    }
+
+    fn import_settings(&mut self) -> anyhow::Result<Vec<String>> {
+        let (mut imported, migrations) =
+            AppConfig::import_from(Path::new(self.settings_path_input.trim()))?;
+        // Exports never carry the bridge token, so importing one keeps the current token.
+        if imported.server_auth_token.is_empty() {
+            imported.server_auth_token = config::lock(&self.config).server_auth_token.clone();
+        }
+        self.replace_config(imported)?;
+        Ok(migrations)
     }
 
     fn drain_events(&mut self) {
         while let Ok(event) = self.events.try_recv() {
             match event {
-                AppEvent::Info(msg) => self.logs.push(format!("INFO: {msg}")),
-                AppEvent::Warning(msg) => self.logs.push(format!("WARN: {msg}")),
-                AppEvent::Error(msg) => self.logs.push(format!("ERR: {msg}")),
+                AppEvent::Info(msg) => self.push_log(LogLevel::Info, format!("INFO: {msg}")),
+                AppEvent::Warning(msg) => self.push_log(LogLevel::Warn, format!("WARN: {msg}")),
+                AppEvent::Error(msg) => self.push_log(LogLevel::Error, format!("ERR: {msg}")),
                 AppEvent::Listening(v) => self.listening = v,
+                AppEvent::CaptureLatched(v) => self.latched = v,
                 AppEvent::SttBusy(v) => self.stt_busy = v,
-                AppEvent::TtsBusy(v) => self.tts_busy = v,
+                AppEvent::TtsBusy(v) => {
+                    self.tts_busy = v;
+                    self.recorder.pause_monitor(v);
+                }
                 AppEvent::LastTranscript(text) => self.last_transcript = text,
+                AppEvent::Metric { stage, ms } => {
+                    self.push_log(LogLevel::Info, format!("INFO: {stage} latency: {ms}ms"));
+                    self.last_metrics.insert(stage, ms);
+                }
+                AppEvent::HotkeyListenerFailed(msg) => self.hotkey_error = Some(msg),
+                AppEvent::SttTestResult {
+                    provider,
+                    seconds,
+                    outcome,
+                } => {
+                    let (summary, output) = match outcome {
+                        Ok(text) if text.is_empty() => ("no speech detected", text),
+                        Ok(text) => ("ok", text),
+                        Err(e) => ("failed", e),
+                    };
+                    self.stt_test_summary =
+                        format!("{seconds:.2}s audio via {provider}: {summary}");
+                    self.stt_test_output = output;
+                }
                 AppEvent::LastSpoken(text) => self.last_spoken = text,
                 AppEvent::ServerOnline(addr) => self.endpoint = addr,
                 AppEvent::ServerOffline => self.endpoint = "Disabled".to_string(),
+                AppEvent::ConfigChanged => {
+                    let cfg = config::lock(&self.config).clone();
+                    self.load_drafts(&cfg);
+                }
+            }
+        }
+        if let Some(rx) = &self.diagnostics_rx {
+            loop {
+                match rx.try_recv() {
+                    Ok(step) => self.diagnostics.push(step),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.diagnostics_rx = None;
+                        break;
+                    }
+                }
             }
         }
-        if self.logs.len() > 300 {
-            let keep = self.logs.split_off(self.logs.len().saturating_sub(300));
+        if self.logs.len() > self.log_max_lines_draft {
+            let keep = self
+                .logs
+                .split_off(self.logs.len().saturating_sub(self.log_max_lines_draft));
             self.logs = keep;
         }
     }
+
+    fn input_capture_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        search.row(ui, "Input gain", |ui| {
+            let slider = egui::Slider::new(
+                &mut self.input_gain_draft,
+                audio::MIN_INPUT_GAIN..=audio::MAX_INPUT_GAIN,
+            )
+            .fixed_decimals(2);
+            if ui.add(slider).changed() {
+                self.recorder.set_input_gain(self.input_gain_draft);
+            }
+        });
+        search.row(ui, "Level", |ui| {
+            let level = self.recorder.input_level();
+            ui.add(
+                egui::ProgressBar::new(level)
+                    .desired_width(160.0)
+                    .text(if level >= 0.99 { "clipping" } else { "" }),
+            );
+        });
+        if search.shows("Monitor Mic") {
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut self.monitoring, "Monitor Mic")
+                    .on_hover_text("Play the live input on the default output device.")
+                    .changed()
+                {
+                    self.set_monitoring(self.monitoring);
+                }
+                ui.label("Volume");
+                let slider = egui::Slider::new(
+                    &mut self.monitor_volume_draft,
+                    0.0..=audio::MAX_MONITOR_VOLUME,
+                )
+                .fixed_decimals(2);
+                if ui.add(slider).changed() {
+                    self.recorder.set_monitor_volume(self.monitor_volume_draft);
+                }
+                if self.monitoring && self.tts_busy {
+                    ui.small("paused while speaking");
+                }
+            });
+            if self.monitoring && self.monitor_on_speakers {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "Output doesn't look like headphones; monitoring through speakers will feed back.",
+                );
+            }
+        }
+        search.row(ui, "Downmix", |ui| {
+            let before = self.downmix_mode_draft;
+            let channel = match before {
+                DownmixMode::Channel(n) => n,
+                _ => 1,
+            };
+            egui::ComboBox::from_id_salt("downmix_mode")
+                .selected_text(match before {
+                    DownmixMode::Channel(_) => "channel".to_string(),
+                    mode => mode.label(),
+                })
+                .show_ui(ui, |ui| {
+                    for mode in [DownmixMode::Average, DownmixMode::Left, DownmixMode::Right] {
+                        ui.selectable_value(&mut self.downmix_mode_draft, mode, mode.label());
+                    }
+                    if ui
+                        .selectable_label(matches!(before, DownmixMode::Channel(_)), "channel")
+                        .clicked()
+                    {
+                        self.downmix_mode_draft = DownmixMode::Channel(channel);
+                    }
+                });
+            if let DownmixMode::Channel(n) = &mut self.downmix_mode_draft {
+                ui.add(egui::DragValue::new(n).range(1..=32));
+            }
+            if self.downmix_mode_draft != before {
+                self.recorder.set_downmix(self.downmix_mode_draft);
+            }
+        });
+        search.row(ui, "Pre-roll (ms)", |ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.preroll_ms_draft).range(0..=audio::MAX_PREROLL_MS),
+            )
+            .on_hover_text(
+                "Audio kept from just before the hotkey goes down, \
+                 so the first word isn't clipped",
+            );
+        });
+        search.row(ui, "Hotkey", |ui| {
+            ui.text_edit_singleline(&mut self.hotkey_draft);
+        });
+        ui.small(format!("Backend: {}", hotkey::BACKEND));
+        if let Some(error) = &self.hotkey_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+        search.row(ui, "Cancel Hotkey", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.cancel_hotkey_draft)
+                    .hint_text("e.g. esc (empty = off)"),
+            );
+        });
+        search.row(ui, "Mode", |ui| {
+            egui::ComboBox::from_id_salt("hotkey_mode")
+                .selected_text(self.hotkey_mode_draft.label())
+                .show_ui(ui, |ui| {
+                    for mode in [
+                        HotkeyMode::Hold,
+                        HotkeyMode::Toggle,
+                        HotkeyMode::DoubleTapLatch,
+                    ] {
+                        ui.selectable_value(&mut self.hotkey_mode_draft, mode, mode.label());
+                    }
+                });
+        });
+        if self.hotkey_mode_draft == HotkeyMode::DoubleTapLatch {
+            search.row(ui, "Double-tap window (ms)", |ui| {
+                ui.add(egui::DragValue::new(&mut self.double_tap_ms_draft).range(100..=1000));
+            });
+        }
+        search.row(ui, "Ignore captures shorter than (ms)", |ui| {
+            ui.add(egui::DragValue::new(&mut self.min_capture_ms_draft).range(0..=2000));
+        });
+        search.row(ui, "Cooldown after a capture (ms)", |ui| {
+            ui.add(egui::DragValue::new(&mut self.capture_cooldown_ms_draft).range(0..=2000));
+            ui.small("0 = off");
+        });
+        search.row(ui, "Keep recording after release (ms)", |ui| {
+            ui.add(egui::DragValue::new(&mut self.release_grace_ms_draft).range(0..=2000));
+            ui.small("0 = off");
+        });
+        search.checkbox(
+            ui,
+            &mut self.cue_sounds_draft,
+            "Beep on start/stop listening",
+        );
+        ui.small("Hotkey changes apply on save.");
+    }
+
+    fn hotkey_binding_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        ui.small(
+            "Extra hotkeys: dictate with another STT provider/model, speak a phrase, or cancel.",
+        );
+        let mut remove = None;
+        for (idx, binding) in self.hotkey_bindings_draft.iter_mut().enumerate() {
+            let label = format!(
+                "{} {} {} {} {}",
+                binding.hotkey,
+                binding.action.label(),
+                binding.stt_model,
+                binding.persona,
+                binding.message
+            );
+            if !search.shows(&label) {
+                continue;
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut binding.hotkey)
+                        .desired_width(100.0)
+                        .hint_text("e.g. ctrl+alt+d"),
+                );
+                egui::ComboBox::from_id_salt(("binding_action", idx))
+                    .selected_text(binding.action.label())
+                    .show_ui(ui, |ui| {
+                        for action in [
+                            BindingAction::Dictate,
+                            BindingAction::Speak,
+                            BindingAction::SpeakLast,
+                            BindingAction::Cancel,
+                        ] {
+                            ui.selectable_value(&mut binding.action, action, action.label());
+                        }
+                    });
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(idx);
+                }
+            });
+            match binding.action {
+                BindingAction::Dictate => {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt(("binding_provider", idx))
+                            .selected_text(binding.stt_provider.map_or("default", provider_label))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut binding.stt_provider, None, "default");
+                                for provider in [Provider::Groq, Provider::OpenAi, Provider::Xai] {
+                                    ui.selectable_value(
+                                        &mut binding.stt_provider,
+                                        Some(provider),
+                                        provider_label(provider),
+                                    );
+                                }
+                            });
+                        ui.add(
+                            egui::TextEdit::singleline(&mut binding.stt_model)
+                                .desired_width(160.0)
+                                .hint_text("model (empty = default)"),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut binding.stt_language)
+                                .desired_width(60.0)
+                                .hint_text("language"),
+                        );
+                    });
+                }
+                BindingAction::Speak => {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::TextEdit::singleline(&mut binding.persona)
+                                .desired_width(80.0)
+                                .hint_text("persona"),
+                        );
+                        ui.add(
+                            egui::TextEdit::singleline(&mut binding.message)
+                                .desired_width(180.0)
+                                .hint_text("message"),
+                        );
+                    });
+                }
+                BindingAction::SpeakLast => {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut binding.persona)
+                            .desired_width(80.0)
+                            .hint_text("persona"),
+                    );
+                }
+                BindingAction::Cancel => {}
+            }
+        }
+        if let Some(idx) = remove {
+            self.hotkey_bindings_draft.remove(idx);
+        }
+        if ui.button("Add Binding").clicked() {
+            self.hotkey_bindings_draft.push(HotkeyBinding::default());
+        }
+        ui.small("Bindings apply on save.");
+    }
+
+    fn audio_input_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        search.checkbox(
+            ui,
+            &mut self.noise_gate_enabled_draft,
+            "Noise gate + hum filter",
+        );
+        ui.add_enabled_ui(self.noise_gate_enabled_draft, |ui| {
+            search.row(ui, "Gate threshold", |ui| {
+                ui.add(egui::DragValue::new(&mut self.noise_gate_threshold_draft).range(0..=8000));
+            });
+        });
+        search.checkbox(
+            ui,
+            &mut self.vad_enabled_draft,
+            "Auto-split dictation on pauses (VAD)",
+        );
+        ui.add_enabled_ui(self.vad_enabled_draft, |ui| {
+            search.row(ui, "Pause length (ms)", |ui| {
+                ui.add(egui::DragValue::new(&mut self.vad_silence_ms_draft).range(200..=5000));
+            });
+            search.row(ui, "Speech threshold", |ui| {
+                ui.add(egui::DragValue::new(&mut self.vad_threshold_draft).range(50..=8000));
+            });
+        });
+    }
+
+    fn stt_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        search.checkbox(
+            ui,
+            &mut self.local_whisper_draft,
+            "Use local whisper.cpp (offline)",
+        );
+        if self.local_whisper_draft {
+            if !cfg!(feature = "local-whisper") {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "This build has no local whisper; rebuild with --features local-whisper.",
+                );
+            }
+            search.row(ui, "Model path", |ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.local_whisper_model_draft)
+                        .hint_text("e.g. ggml-base.en.bin"),
+                );
+            });
+        }
+        search.row(ui, "Provider", |ui| {
+            let old_stt_provider = self.stt_provider_draft;
+            egui::ComboBox::from_id_salt("stt_provider")
+                .selected_text(provider_label(self.stt_provider_draft))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.stt_provider_draft, Provider::Groq, "groq");
+                    ui.selectable_value(&mut self.stt_provider_draft, Provider::OpenAi, "openai");
+                });
+            missing_key_indicator(ui, self.stt_provider_draft);
+            if self.stt_provider_draft != old_stt_provider {
+                self.stt_model_draft = self
+                    .stt_model_by_provider_draft
+                    .get(provider_label(self.stt_provider_draft))
+                    .cloned()
+                    .unwrap_or_else(|| self.stt_model_draft.clone());
+                // Keep an unsaved pick for the provider being left.
+                self.stt_language_by_provider_draft.insert(
+                    provider_label(old_stt_provider).to_string(),
+                    self.stt_language_draft.clone(),
+                );
+                if let Some(language) = self
+                    .stt_language_by_provider_draft
+                    .get(provider_label(self.stt_provider_draft))
+                {
+                    self.stt_language_draft = language.clone();
+                }
+            }
+        });
+        search.row(ui, "Model", |ui| {
+            let models = {
+                let cfg = config::lock(&self.config);
+                cfg.stt_available_models(self.stt_provider_draft)
+            };
+            egui::ComboBox::from_id_salt("stt_model")
+                .selected_text(self.stt_model_draft.clone())
+                .show_ui(ui, |ui| {
+                    for model in models {
+                        ui.selectable_value(&mut self.stt_model_draft, model.clone(), model);
+                    }
+                });
+        });
+        search.row(ui, "Language", |ui| {
+            egui::ComboBox::from_id_salt("stt_language")
+                .selected_text(stt_language_label(&self.stt_language_draft))
+                .show_ui(ui, |ui| {
+                    let current = self.stt_language_draft.clone();
+                    if !STT_LANGUAGES.iter().any(|(code, _)| *code == current) {
+                        ui.selectable_value(
+                            &mut self.stt_language_draft,
+                            current.clone(),
+                            stt_language_label(&current),
+                        );
+                    }
+                    for (code, name) in STT_LANGUAGES {
+                        ui.selectable_value(&mut self.stt_language_draft, code.to_string(), *name);
+                    }
+                });
+        });
+        ui.label("Prompt (vocabulary hints)");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.stt_prompt_draft)
+                .desired_rows(2)
+                .hint_text("e.g. Kubernetes, kubectl, async/await"),
+        );
+        search.row(ui, "Min confidence", |ui| {
+            ui.add(
+                egui::DragValue::new(&mut self.stt_min_confidence_draft)
+                    .range(0.0..=1.0)
+                    .speed(0.01),
+            );
+            ui.small("0 = off; whisper models only");
+        });
+        if search.shows("Stream partial transcripts") {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.stt_streaming_draft, "Stream partial transcripts");
+                ui.small("OpenAI gpt-4o transcribe models");
+            });
+        }
+        search.row(ui, "Upload format", |ui| {
+            egui::ComboBox::from_id_salt("stt_upload_format")
+                .selected_text(self.stt_upload_format_draft.label())
+                .show_ui(ui, |ui| {
+                    for format in [UploadFormat::Wav, UploadFormat::Flac] {
+                        ui.selectable_value(
+                            &mut self.stt_upload_format_draft,
+                            format,
+                            format.label(),
+                        );
+                    }
+                });
+            ui.small("FLAC is about half the size");
+        });
+        search.row(ui, "Queued captures", |ui| {
+            egui::ComboBox::from_id_salt("stt_coalesce")
+                .selected_text(self.stt_coalesce_draft.label())
+                .show_ui(ui, |ui| {
+                    for mode in [SttCoalesce::Fifo, SttCoalesce::Merge, SttCoalesce::Latest] {
+                        ui.selectable_value(&mut self.stt_coalesce_draft, mode, mode.label());
+                    }
+                });
+            ui.small("while a transcription is running");
+        });
+        search.row(ui, "Request timeout (s)", |ui| {
+            ui.add(egui::DragValue::new(&mut self.http_timeout_secs_draft).range(5..=300));
+            ui.small("STT and TTS HTTP calls");
+        });
+        search.row(ui, "Save recordings to", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.save_recordings_dir_draft)
+                    .hint_text("folder (empty = off)"),
+            );
+        });
+    }
+
+    fn endpoint_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        ui.small(
+            "Base URLs for proxies or OpenAI-compatible servers; blank uses the official API.",
+        );
+        ui.label("Speech to text");
+        for provider in [Provider::Groq, Provider::OpenAi] {
+            if search.shows(&format!("Speech to text {}", provider_label(provider))) {
+                base_url_field(ui, &mut self.stt_base_urls_draft, provider);
+            }
+        }
+        ui.label("Text to speech");
+        for provider in [
+            Provider::Xai,
+            Provider::OpenAi,
+            Provider::Groq,
+            Provider::ElevenLabs,
+        ] {
+            if search.shows(&format!("Text to speech {}", provider_label(provider))) {
+                base_url_field(ui, &mut self.tts_base_urls_draft, provider);
+            }
+        }
+    }
+
+    fn replacement_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        ui.small(
+            "Case-insensitive literal matches, applied in order before paste. Tick regex to match a pattern instead; $1 in the replacement inserts a group.",
+        );
+        let mut remove = None;
+        for (idx, rule) in self.replacements_draft.iter_mut().enumerate() {
+            if !search.shows(&format!("{} {}", rule.find, rule.replace)) {
+                continue;
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut rule.find)
+                        .desired_width(120.0)
+                        .hint_text("find"),
+                );
+                ui.label("->");
+                ui.add(
+                    egui::TextEdit::singleline(&mut rule.replace)
+                        .desired_width(120.0)
+                        .hint_text("replace"),
+                );
+                ui.checkbox(&mut rule.regex, "regex");
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(idx);
+                }
+            });
+            if rule.regex
+                && !rule.find.is_empty()
+                && let Err(e) = transcript::compile_rule(&rule.find)
+            {
+                ui.colored_label(ui.visuals().error_fg_color, e);
+            }
+        }
+        if let Some(idx) = remove {
+            self.replacements_draft.remove(idx);
+        }
+        if ui.button("Add Rule").clicked() {
+            self.replacements_draft.push(ReplacementRule::default());
+        }
+        ui.label("Strip leading words (one per line)");
+        ui.add(
+            egui::TextEdit::multiline(&mut self.strip_prefixes_draft)
+                .desired_rows(2)
+                .hint_text("e.g. computer"),
+        );
+        ui.small("Removed from the start of a transcript, with the comma or space after it.");
+    }
+
+    fn injection_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        search.checkbox(
+            ui,
+            &mut self.auto_capitalize_draft,
+            "Capitalize sentence starts",
+        );
+        ui.add_enabled_ui(self.auto_capitalize_draft, |ui| {
+            search.checkbox(ui, &mut self.auto_period_draft, "Add a period if missing");
+        });
+        search
+            .checkbox(
+                ui,
+                &mut self.speak_transcript_back_draft,
+                "Read pasted text aloud",
+            )
+            .on_hover_text(
+                "Speaks each pasted transcript with the TTS voice; \
+                 set TTS \"While recording\" to duck or pause",
+            );
+        search.row(ui, "Append after text", |ui| {
+            egui::ComboBox::from_id_salt("inject_suffix")
+                .selected_text(inject_suffix_label(&self.inject_suffix_draft))
+                .show_ui(ui, |ui| {
+                    for (suffix, label) in INJECT_SUFFIXES {
+                        ui.selectable_value(
+                            &mut self.inject_suffix_draft,
+                            suffix.to_string(),
+                            *label,
+                        );
+                    }
+                });
+        });
+        search.row(ui, "Filter through command", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.inject_command_draft)
+                    .hint_text("empty = paste as-is"),
+            )
+            .on_hover_text(
+                "The transcript goes to the command's stdin and \
+                 its stdout is pasted instead. On failure or \
+                 timeout the raw transcript is pasted.",
+            );
+        });
+        search.row(ui, "Paste shortcut", |ui| {
+            egui::ComboBox::from_id_salt("paste_shortcut")
+                .selected_text(self.paste_shortcut_draft.clone())
+                .show_ui(ui, |ui| {
+                    for preset in PASTE_SHORTCUTS {
+                        ui.selectable_value(
+                            &mut self.paste_shortcut_draft,
+                            preset.to_string(),
+                            *preset,
+                        );
+                    }
+                });
+            ui.add(egui::TextEdit::singleline(&mut self.paste_shortcut_draft).desired_width(110.0));
+        });
+        ui.small("Terminals often need ctrl+shift+v; macOS uses cmd+v.");
+        search.row(ui, "Paste", |ui| {
+            egui::ComboBox::from_id_salt("inject_placement")
+                .selected_text(self.inject_placement_draft.label())
+                .show_ui(ui, |ui| {
+                    for placement in [
+                        InjectPlacement::Cursor,
+                        InjectPlacement::EndOfLine,
+                        InjectPlacement::ReplaceSelection,
+                    ] {
+                        ui.selectable_value(
+                            &mut self.inject_placement_draft,
+                            placement,
+                            placement.label(),
+                        );
+                    }
+                });
+        });
+        search.row(ui, "Clipboard settle delay (ms)", |ui| {
+            ui.add(egui::DragValue::new(&mut self.inject_settle_ms_draft).range(20..=1000));
+        });
+        if cfg!(target_os = "windows") {
+            search.row(ui, "Wait for focus to settle (ms)", |ui| {
+                ui.add(egui::DragValue::new(&mut self.inject_focus_stable_ms_draft).range(0..=500));
+                ui.label("up to");
+                ui.add(
+                    egui::DragValue::new(&mut self.inject_focus_timeout_ms_draft).range(0..=3000),
+                );
+            });
+        }
+        search.checkbox(
+            ui,
+            &mut self.inject_verify_draft,
+            "Verify clipboard before pasting (retry once)",
+        );
+        search
+            .checkbox(
+                ui,
+                &mut self.inject_submit_draft,
+                "Press Enter after pasting (chat apps)",
+            )
+            .on_hover_text(
+                "Sends the message in Slack, Discord, ChatGPT and similar. \
+                 Leave off for editors, where Enter just adds a line.",
+            );
+        let allowlist_label = "Only paste into windows whose title contains";
+        if search.shows(&format!(
+            "{allowlist_label} {}",
+            self.inject_allowlist_draft
+        )) {
+            ui.label(allowlist_label);
+            ui.add(
+                egui::TextEdit::multiline(&mut self.inject_allowlist_draft)
+                    .desired_rows(2)
+                    .hint_text("one per line (empty = any window)"),
+            );
+        }
+    }
+
+    fn tts_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        search.checkbox(
+            ui,
+            &mut self.tts_bridge_enabled_draft,
+            "Enable internal TTS bridge server",
+        );
+        search.row(ui, "TTS Provider", |ui| {
+            let old_tts_provider = self.tts_provider_draft;
+            egui::ComboBox::from_id_salt("tts_provider")
+                .selected_text(provider_label(self.tts_provider_draft))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.tts_provider_draft, Provider::Xai, "xai");
+                    ui.selectable_value(&mut self.tts_provider_draft, Provider::OpenAi, "openai");
+                    ui.selectable_value(&mut self.tts_provider_draft, Provider::Groq, "groq");
+                    ui.selectable_value(
+                        &mut self.tts_provider_draft,
+                        Provider::ElevenLabs,
+                        "elevenlabs",
+                    );
+                });
+            missing_key_indicator(ui, self.tts_provider_draft);
+            if self.tts_provider_draft != old_tts_provider {
+                self.tts_voice_draft = self
+                    .tts_voice_by_provider_draft
+                    .get(provider_label(self.tts_provider_draft))
+                    .cloned()
+                    .unwrap_or_else(|| self.tts_voice_draft.clone());
+            }
+        });
+        search.row(ui, "Voice", |ui| {
+            if self.tts_provider_draft == Provider::ElevenLabs {
+                // Account-specific IDs, so there is no fixed list to offer.
+                ui.add(egui::TextEdit::singleline(&mut self.tts_voice_draft).hint_text("voice ID"));
+                return;
+            }
+            egui::ComboBox::from_id_salt("tts_voice")
+                .selected_text(self.tts_voice_draft.clone())
+                .show_ui(ui, |ui| {
+                    for voice in tts_voices_for_provider(self.tts_provider_draft) {
+                        let v = voice.to_string();
+                        ui.selectable_value(&mut self.tts_voice_draft, v.clone(), v);
+                    }
+                });
+        });
+        search.row(ui, "Model", |ui| {
+            let provider = self.tts_provider_draft;
+            let model = match provider {
+                Provider::Xai => &mut self.xai_realtime_model_draft,
+                Provider::OpenAi => &mut self.openai_tts_model_draft,
+                Provider::Groq => &mut self.groq_tts_model_draft,
+                Provider::ElevenLabs => &mut self.elevenlabs_tts_model_draft,
+            };
+            ui.add(
+                egui::TextEdit::singleline(model)
+                    .desired_width(220.0)
+                    .hint_text("model id"),
+            );
+            egui::ComboBox::from_id_salt("tts_model_known")
+                .selected_text("known")
+                .width(70.0)
+                .show_ui(ui, |ui| {
+                    for known in tts_models_for_provider(provider) {
+                        ui.selectable_value(model, known.to_string(), known);
+                    }
+                });
+        });
+        ui.label("Voice aliases");
+        let mut remove = None;
+        for (idx, (name, alias)) in self.voice_aliases_draft.iter_mut().enumerate() {
+            let label = format!(
+                "Voice aliases {name} {} {}",
+                provider_label(alias.provider),
+                alias.voice
+            );
+            if !search.shows(&label) {
+                continue;
+            }
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(name)
+                        .desired_width(90.0)
+                        .hint_text("name"),
+                );
+                ui.label("->");
+                egui::ComboBox::from_id_salt(("voice_alias", idx))
+                    .selected_text(provider_label(alias.provider))
+                    .width(90.0)
+                    .show_ui(ui, |ui| {
+                        for provider in [
+                            Provider::Xai,
+                            Provider::OpenAi,
+                            Provider::Groq,
+                            Provider::ElevenLabs,
+                        ] {
+                            ui.selectable_value(
+                                &mut alias.provider,
+                                provider,
+                                provider_label(provider),
+                            );
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::singleline(&mut alias.voice)
+                        .desired_width(90.0)
+                        .hint_text("voice"),
+                );
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        }
+        if let Some(idx) = remove {
+            self.voice_aliases_draft.remove(idx);
+        }
+        if ui.button("Add Alias").clicked() {
+            self.voice_aliases_draft.push((
+                String::new(),
+                VoiceAlias {
+                    provider: self.tts_provider_draft,
+                    voice: self.tts_voice_draft.clone(),
+                },
+            ));
+        }
+        search.row(ui, "HTTP Port", |ui| {
+            ui.add(egui::DragValue::new(&mut self.server_port_draft).range(1025..=65535));
+            if ui
+                .button("Apply Server Settings")
+                .on_hover_text(
+                    "Starts, stops or moves the server to match these settings \
+                     now. Save Configuration also keeps them.",
+                )
+                .clicked()
+            {
+                self.apply_server_settings();
+            }
+        });
+        search.checkbox(
+            ui,
+            &mut self.show_endpoint_text_draft,
+            "Show endpoint text in UI",
+        );
+        search.checkbox(ui, &mut self.tts_normalize_draft, "Normalize speech volume");
+        ui.add_enabled_ui(self.tts_normalize_draft, |ui| {
+            search.row(ui, "Target peak (dBFS)", |ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.tts_target_peak_draft)
+                        .range(-30.0..=0.0)
+                        .speed(0.5),
+                );
+            });
+        });
+        search
+            .checkbox(
+                ui,
+                &mut self.tts_keep_output_open_draft,
+                "Keep audio output open between utterances",
+            )
+            .on_hover_text(
+                "Avoids the device-open delay and click; \
+                 some Bluetooth headsets stay awake while it's open",
+            );
+        search.row(ui, "While recording", |ui| {
+            egui::ComboBox::from_id_salt("duck_tts_on_listen")
+                .selected_text(self.duck_tts_on_listen_draft.label())
+                .show_ui(ui, |ui| {
+                    for mode in [ListenDuck::Off, ListenDuck::Duck, ListenDuck::Pause] {
+                        ui.selectable_value(&mut self.duck_tts_on_listen_draft, mode, mode.label());
+                    }
+                });
+            ui.small("keeps speech out of the mic");
+        });
+        search.row(ui, "Auth Token", |ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.server_auth_token_draft)
+                    .password(true)
+                    .hint_text("empty = off"),
+            );
+        });
+        search.row(ui, "/speak limit per minute", |ui| {
+            ui.add(egui::DragValue::new(&mut self.speak_rate_per_min_draft).range(0..=600));
+            ui.small("0 = unlimited");
+        });
+        search.row(ui, "xAI Delivery Style", |ui| {
+            ui.add_enabled_ui(self.tts_provider_draft == Provider::Xai, |ui| {
+                ui.text_edit_singleline(&mut self.xai_style_draft);
+            });
+        });
+        ui.add_enabled_ui(self.tts_provider_draft == Provider::Xai, |ui| {
+            search
+                .checkbox(ui, &mut self.tts_verbatim_draft, "Speak text verbatim")
+                .on_hover_text(
+                    "Off: the delivery style becomes the instruction and the model may rephrase.",
+                );
+        });
+        if self.tts_provider_draft != Provider::Xai {
+            ui.small("Only xAI realtime currently supports style prompting.");
+        }
+    }
+
+    fn tools_section(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) {
+        ui.horizontal(|ui| {
+            if ui.button("Manual Capture + STT").clicked() {
+                let recorder = self.recorder.clone();
+                let stt_tx = self.stt_tx.clone();
+                std::thread::spawn(move || {
+                    recorder.start_capture();
+                    std::thread::sleep(Duration::from_millis(1300));
+                    let audio = recorder.stop_capture();
+                    if !audio.is_empty() {
+                        let _ = stt_tx.send(SttJob::new(audio));
+                    }
+                });
+            }
+            ui.label("Records ~1.3s then transcribes.");
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Test STT (no paste)").clicked() {
+                let recorder = self.recorder.clone();
+                let stt_tx = self.stt_tx.clone();
+                self.stt_test_summary = "Recording ~3s...".to_string();
+                self.stt_test_output.clear();
+                std::thread::spawn(move || {
+                    recorder.start_test_capture();
+                    std::thread::sleep(STT_TEST_CAPTURE);
+                    let audio = recorder.stop_capture();
+                    if !audio.is_empty() {
+                        let _ = stt_tx.send(SttJob::test(audio));
+                    }
+                });
+            }
+            ui.label(&self.stt_test_summary);
+        });
+        ui.add(
+            egui::TextEdit::multiline(&mut self.stt_test_output.as_str())
+                .desired_rows(2)
+                .desired_width(f32::INFINITY)
+                .hint_text("Test transcript shows here; nothing is pasted."),
+        );
+        ui.separator();
+        ui.horizontal(|ui| {
+            let running = self.diagnostics_rx.is_some();
+            if ui
+                .add_enabled(
+                    !running && !self.listening,
+                    egui::Button::new("Run Diagnostics"),
+                )
+                .clicked()
+            {
+                self.diagnostics.clear();
+                self.diagnostics_rx = Some(diagnostics::spawn(
+                    self.config.clone(),
+                    self.recorder.clone(),
+                    self.stt_tx.clone(),
+                    self.tts_tx.clone(),
+                ));
+            }
+            if running {
+                ui.label("Running; speak normally for the mic check...");
+            } else {
+                ui.label("Checks mic, keys, STT and TTS in turn.");
+            }
+        });
+        for step in &self.diagnostics {
+            ui.horizontal(|ui| {
+                match &step.outcome {
+                    Ok(_) => ui.label("PASS"),
+                    Err(_) => ui.colored_label(ui.visuals().error_fg_color, "FAIL"),
+                };
+                ui.label(step.name);
+                let (Ok(detail) | Err(detail)) = &step.outcome;
+                ui.small(detail);
+            });
+        }
+        ui.separator();
+        ui.label("Voice test:");
+        search.row(ui, "Persona", |ui| {
+            ui.text_edit_singleline(&mut self.persona_input);
+        });
+        ui.text_edit_singleline(&mut self.message_input);
+        ui.horizontal(|ui| {
+            if ui.button("Speak Test").clicked() {
+                self.speak_test(self.message_input.clone());
+            }
+            let last = self.last_transcript.trim().to_string();
+            if ui
+                .add_enabled(!last.is_empty(), egui::Button::new("Speak Last Transcript"))
+                .clicked()
+            {
+                self.speak_test(last);
+            }
+        });
+    }
+
+    fn logs_section(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!("Last Spoken: {}", self.last_spoken));
+        ui.horizontal(|ui| {
+            if ui.button("Copy Logs").clicked() {
+                let text = self.logs.join("\n");
+                let status = match arboard::Clipboard::new()
+                    .and_then(|mut clipboard| clipboard.set_text(text))
+                {
+                    Ok(()) => format!("Copied {} log lines.", self.logs.len()),
+                    Err(e) => format!("Copy failed: {e}"),
+                };
+                self.last_save_status = Some((status, Instant::now()));
+            }
+            if ui.button("Clear Logs").clicked() {
+                self.logs.clear();
+            }
+        });
+        ui.horizontal(|ui| {
+            let before = (self.log_level_draft, self.log_max_lines_draft);
+            ui.label("Show");
+            egui::ComboBox::from_id_salt("log_level")
+                .selected_text(self.log_level_draft.label())
+                .show_ui(ui, |ui| {
+                    for level in [LogLevel::Error, LogLevel::Warn, LogLevel::Info] {
+                        ui.selectable_value(&mut self.log_level_draft, level, level.label());
+                    }
+                });
+            ui.label("Keep lines");
+            ui.add(egui::DragValue::new(&mut self.log_max_lines_draft).range(50..=5000));
+            if (self.log_level_draft, self.log_max_lines_draft) != before {
+                self.save_log_view();
+            }
+        });
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .max_height(140.0)
+            .show(ui, |ui| {
+                for line in self.logs.iter().rev().take(80) {
+                    ui.monospace(line);
+                }
+            });
+    }
+
+    // Rows after the last subsection; true when Save Configuration was clicked.
+    fn general_settings(&mut self, ui: &mut egui::Ui, search: &SettingsSearch) -> bool {
+        let mut save = false;
+        search.row(ui, "Theme", |ui| {
+            egui::ComboBox::from_id_salt("theme")
+                .selected_text(self.theme_draft.label())
+                .show_ui(ui, |ui| {
+                    for theme in [Theme::System, Theme::Dark, Theme::Light] {
+                        ui.selectable_value(&mut self.theme_draft, theme, theme.label());
+                    }
+                });
+        });
+        search.row(ui, "Recording color", |ui| {
+            ui.color_edit_button_srgb(&mut self.listen_accent_draft);
+        });
+        search.checkbox(ui, &mut self.always_on_top_draft, "Keep window on top");
+        search
+            .checkbox(
+                ui,
+                &mut self.auto_resize_draft,
+                "Fit window height to content",
+            )
+            .on_hover_text("Off keeps the window at the size you leave it");
+        search.checkbox(ui, &mut self.log_to_file_draft, "Write logs to file");
+        if self.log_to_file_draft
+            && let Ok(path) = logging::log_path()
+        {
+            ui.small(path.display().to_string());
+        }
+        search
+            .checkbox(ui, &mut self.mock_mode_draft, "Mock providers")
+            .on_hover_text(
+                "Offline testing: canned transcripts and a beep instead of \
+                 speech; no API keys or network",
+            );
+        if ui.button("Save Configuration").clicked() {
+            save = true;
+        }
+        ui.separator();
+        search.row(ui, "Settings file", |ui| {
+            ui.text_edit_singleline(&mut self.settings_path_input);
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Export Settings").clicked() {
+                let status = match self.export_settings() {
+                    Ok(()) => format!("Exported settings to {}.", self.settings_path_input.trim()),
+                    Err(e) => format!("Export failed: {e}"),
+                };
+                self.last_save_status = Some((status, Instant::now()));
+            }
+            if ui.button("Import Settings").clicked() {
+                let status = match self.import_settings() {
+                    Ok(migrations) => {
+                        for note in migrations {
+                            self.push_log(LogLevel::Info, format!("INFO: {note}"));
+                        }
+                        "Imported and saved settings.".to_string()
+                    }
+                    Err(e) => format!("Import failed: {e}"),
+                };
+                self.last_save_status = Some((status, Instant::now()));
+            }
+        });
+        ui.small("API keys and the bridge token are never exported.");
+        ui.separator();
+        let armed = self
+            .reset_armed_at
+            .is_some_and(|at| at.elapsed() < RESET_CONFIRM_WINDOW);
+        let reset_label = if armed {
+            "Click again to confirm reset"
+        } else {
+            "Reset to Defaults"
+        };
+        if ui.button(reset_label).clicked() {
+            if armed {
+                self.reset_armed_at = None;
+                let status = match self.replace_config(AppConfig::default()) {
+                    Ok(()) => "Configuration reset to defaults.".to_string(),
+                    Err(e) => format!("Reset failed: {e}"),
+                };
+                self.last_save_status = Some((status, Instant::now()));
+            } else {
+                self.reset_armed_at = Some(Instant::now());
+            }
+        }
+        save
+    }
 }
 
 impl eframe::App for Push2TypeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.drain_events();
         ctx.request_repaint_after(Duration::from_millis(120));
+        #[cfg(windows)]
+        if let Some(tray) = &self.tray {
+            if tray.quit_requested() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            tray.sync_bridge(config::lock(&self.config).tts_bridge_enabled);
+        }
+        self.restore_window_position(ctx);
+        self.track_window_geometry(ctx);
+        if self.applied_theme != Some(self.theme_draft) {
+            // egui follows the OS light/dark setting itself when given the System preference.
+            ctx.set_theme(match self.theme_draft {
+                Theme::System => egui::ThemePreference::System,
+                Theme::Dark => egui::ThemePreference::Dark,
+                Theme::Light => egui::ThemePreference::Light,
+            });
+            self.applied_theme = Some(self.theme_draft);
+        }
+        if self.applied_always_on_top != Some(self.always_on_top_draft) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                if self.always_on_top_draft {
+                    egui::WindowLevel::AlwaysOnTop
+                } else {
+                    egui::WindowLevel::Normal
+                },
+            ));
+            self.applied_always_on_top = Some(self.always_on_top_draft);
+        }
 
         let mut save_main = false;
         let mut content_height = 280.0f32;
@@ -149,10 +1771,55 @@ impl eframe::App for Push2TypeApp {
             ui.heading("Push2Type Satellite");
             ui.label("Mic -> STT -> Paste");
             ui.label("HTTP -> TTS -> Speakers");
-            ui.monospace(format!("Endpoint: {}", self.endpoint));
+            ui.horizontal(|ui| {
+                ui.monospace(format!("Endpoint: {}", self.endpoint));
+                let online = self.endpoint.starts_with("http");
+                // No QR code beside it: the bridge only listens on 127.0.0.1, which a
+                // phone or another machine can't reach anyway.
+                if ui
+                    .add_enabled(online, egui::Button::new("Copy").small())
+                    .on_hover_text("Copy the /speak URL for agent configs")
+                    .clicked()
+                {
+                    let status = match arboard::Clipboard::new()
+                        .and_then(|mut clipboard| clipboard.set_text(self.endpoint.clone()))
+                    {
+                        Ok(()) => "Copied endpoint URL.".to_string(),
+                        Err(e) => format!("Copy failed: {e}"),
+                    };
+                    self.last_save_status = Some((status, Instant::now()));
+                }
+            });
 
+            let accent = egui::Color32::from_rgb(
+                self.listen_accent_draft[0],
+                self.listen_accent_draft[1],
+                self.listen_accent_draft[2],
+            );
+            if self.listening {
+                // Drawn over everything so it shows however the window is scrolled or sized.
+                ctx.layer_painter(egui::LayerId::new(
+                    egui::Order::Foreground,
+                    egui::Id::new("listen_border"),
+                ))
+                .rect_stroke(
+                    ctx.screen_rect(),
+                    0.0,
+                    egui::Stroke::new(LISTEN_BORDER_WIDTH, accent),
+                    egui::StrokeKind::Inside,
+                );
+            }
             ui.horizontal(|ui| {
-                let mic = if self.listening {
+                let (dot, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                let dot_color = if self.listening {
+                    accent
+                } else {
+                    ui.visuals().weak_text_color()
+                };
+                ui.painter().circle_filled(dot.center(), 4.5, dot_color);
+                let mic = if self.listening && self.latched {
+                    "Mic: Listening (latched)"
+                } else if self.listening {
                     "Mic: Listening"
                 } else {
                     "Mic: Idle"
@@ -172,8 +1839,33 @@ impl eframe::App for Push2TypeApp {
                 ui.monospace(stt);
                 ui.separator();
                 ui.monospace(tts);
+                ui.separator();
+                let mut inject_on = self.inject_enabled_draft;
+                if ui.checkbox(&mut inject_on, "Paste").changed() {
+                    self.set_inject_enabled(inject_on);
+                }
+                if !inject_on {
+                    ui.colored_label(ui.visuals().warn_fg_color, "dry run");
+                }
             });
 
+            let push_text = if self.listening {
+                "Release to Send"
+            } else {
+                "Hold to Talk"
+            };
+            let push = ui
+                .add_sized(
+                    [ui.available_width(), 36.0],
+                    egui::Button::new(push_text).sense(egui::Sense::click_and_drag()),
+                )
+                .on_hover_text("Same as holding the hotkey; no global input permission needed");
+            let held = push.is_pointer_button_down_on();
+            if held != self.push_held {
+                self.push_held = held;
+                self.hotkey_control.set_ui_held(held);
+            }
+
             ui.separator();
             egui::CollapsingHeader::new("Operations")
                 .id_salt("section_ops")
@@ -186,173 +1878,100 @@ impl eframe::App for Push2TypeApp {
                     ));
                     ui.monospace(format!("TTS: {}", provider_label(self.tts_provider_draft)));
                     ui.label(format!("Last Transcript: {}", self.last_transcript));
+                    if let Some(timings) = self.timing_summary() {
+                        ui.small(timings);
+                    }
+                    ui.horizontal(|ui| {
+                        let mut bridge_on = self.tts_bridge_enabled_draft;
+                        if ui.checkbox(&mut bridge_on, "TTS bridge").changed() {
+                            self.set_bridge_enabled(bridge_on);
+                        }
+                        #[cfg(windows)]
+                        if let Some(tray) = &self.tray {
+                            if ui.button("Hide to Tray").clicked() {
+                                tray.hide_window();
+                            }
+                            return;
+                        }
+                        if ui.button("Minimize").clicked() {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+                        }
+                    });
                 });
 
             egui::CollapsingHeader::new("Advanced")
                 .id_salt("section_advanced")
                 .default_open(false)
                 .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Search");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.settings_filter)
+                                .hint_text("e.g. voice, port, hotkey"),
+                        );
+                        if !self.settings_filter.is_empty() && ui.small_button("Clear").clicked() {
+                            self.settings_filter.clear();
+                        }
+                    });
+                    let search = self.search.clone();
+                    if search.begin_frame(&self.settings_filter) {
+                        ctx.request_repaint();
+                    }
                     egui::CollapsingHeader::new("Configuration")
                         .id_salt("section_config")
                         .default_open(false)
+                        .open(search.group("Configuration"))
                         .show(ui, |ui| {
                             egui::CollapsingHeader::new("Input Capture")
                                 .id_salt("cfg_input_capture")
                                 .default_open(false)
-                                .show(ui, |ui| {
-                                    ui.horizontal(|ui| {
-                                        ui.label("Hotkey");
-                                        ui.text_edit_singleline(&mut self.hotkey_draft);
-                                    });
-                                    ui.label("Hotkey changes require app restart.");
-                                });
+                                .open(search.subsection("Input Capture"))
+                                .show(ui, |ui| self.input_capture_settings(ui, &search));
+
+                            egui::CollapsingHeader::new("Hotkey Bindings")
+                                .id_salt("cfg_hotkey_bindings")
+                                .default_open(false)
+                                .open(search.subsection("Hotkey Bindings"))
+                                .show(ui, |ui| self.hotkey_binding_settings(ui, &search));
+
+                            egui::CollapsingHeader::new("Audio Input")
+                                .id_salt("cfg_audio")
+                                .default_open(false)
+                                .open(search.subsection("Audio Input"))
+                                .show(ui, |ui| self.audio_input_settings(ui, &search));
 
                             egui::CollapsingHeader::new("Speech To Text")
                                 .id_salt("cfg_stt")
                                 .default_open(false)
-                                .show(ui, |ui| {
-                                    ui.horizontal(|ui| {
-                                        let old_stt_provider = self.stt_provider_draft;
-                                        ui.label("Provider");
-                                        egui::ComboBox::from_id_salt("stt_provider")
-                                            .selected_text(provider_label(self.stt_provider_draft))
-                                            .show_ui(ui, |ui| {
-                                                ui.selectable_value(
-                                                    &mut self.stt_provider_draft,
-                                                    Provider::Groq,
-                                                    "groq",
-                                                );
-                                                ui.selectable_value(
-                                                    &mut self.stt_provider_draft,
-                                                    Provider::OpenAi,
-                                                    "openai",
-                                                );
-                                            });
-                                        if self.stt_provider_draft != old_stt_provider {
-                                            self.stt_model_draft = self
-                                                .stt_model_by_provider_draft
-                                                .get(provider_label(self.stt_provider_draft))
-                                                .cloned()
-                                                .unwrap_or_else(|| self.stt_model_draft.clone());
-                                        }
-                                    });
-                                    ui.horizontal(|ui| {
-                                        ui.label("Model");
-                                        let models = {
-                                            let cfg = self.config.lock().expect("config lock");
-                                            cfg.stt_available_models(self.stt_provider_draft)
-                                        };
-                                        egui::ComboBox::from_id_salt("stt_model")
-                                            .selected_text(self.stt_model_draft.clone())
-                                            .show_ui(ui, |ui| {
-                                                for model in models {
-                                                    ui.selectable_value(
-                                                        &mut self.stt_model_draft,
-                                                        model.clone(),
-                                                        model,
-                                                    );
-                                                }
-                                            });
-                                    });
-                                    ui.horizontal(|ui| {
-                                        ui.label("Language");
-                                        ui.text_edit_singleline(&mut self.stt_language_draft);
-                                        ui.label("example: en");
-                                    });
-                                });
+                                .open(search.subsection("Speech To Text"))
+                                .show(ui, |ui| self.stt_settings(ui, &search));
+
+                            egui::CollapsingHeader::new("Advanced Endpoints")
+                                .id_salt("cfg_endpoints")
+                                .default_open(false)
+                                .open(search.subsection("Advanced Endpoints"))
+                                .show(ui, |ui| self.endpoint_settings(ui, &search));
+
+                            egui::CollapsingHeader::new("Transcript Replacements")
+                                .id_salt("cfg_replacements")
+                                .default_open(false)
+                                .open(search.subsection("Transcript Replacements"))
+                                .show(ui, |ui| self.replacement_settings(ui, &search));
+
+                            egui::CollapsingHeader::new("Text Injection")
+                                .id_salt("cfg_injection")
+                                .default_open(false)
+                                .open(search.subsection("Text Injection"))
+                                .show(ui, |ui| self.injection_settings(ui, &search));
 
                             egui::CollapsingHeader::new("Text To Speech + Voice Bridge")
                                 .id_salt("cfg_tts_bridge")
                                 .default_open(false)
-                                .show(ui, |ui| {
-                                    if ui
-                                        .checkbox(
-                                            &mut self.tts_bridge_enabled_draft,
-                                            "Enable internal TTS bridge server",
-                                        )
-                                        .changed()
-                                    {
-                                        self.server_control
-                                            .set_enabled(self.tts_bridge_enabled_draft);
-                                        if !self.tts_bridge_enabled_draft {
-                                            self.endpoint = "Disabled".to_string();
-                                        }
-                                    }
-                                    ui.horizontal(|ui| {
-                                        let old_tts_provider = self.tts_provider_draft;
-                                        ui.label("TTS Provider");
-                                        egui::ComboBox::from_id_salt("tts_provider")
-                                            .selected_text(provider_label(self.tts_provider_draft))
-                                            .show_ui(ui, |ui| {
-                                                ui.selectable_value(
-                                                    &mut self.tts_provider_draft,
-                                                    Provider::Xai,
-                                                    "xai",
-                                                );
-                                                ui.selectable_value(
-                                                    &mut self.tts_provider_draft,
-                                                    Provider::OpenAi,
-                                                    "openai",
-                                                );
-                                                ui.selectable_value(
-                                                    &mut self.tts_provider_draft,
-                                                    Provider::Groq,
-                                                    "groq",
-                                                );
-                                            });
-                                        if self.tts_provider_draft != old_tts_provider {
-                                            self.tts_voice_draft = self
-                                                .tts_voice_by_provider_draft
-                                                .get(provider_label(self.tts_provider_draft))
-                                                .cloned()
-                                                .unwrap_or_else(|| self.tts_voice_draft.clone());
-                                        }
-                                    });
-                                    ui.horizontal(|ui| {
-                                        ui.label("Voice");
-                                        egui::ComboBox::from_id_salt("tts_voice")
-                                            .selected_text(self.tts_voice_draft.clone())
-                                            .show_ui(ui, |ui| {
-                                                for voice in
-                                                    tts_voices_for_provider(self.tts_provider_draft)
-                                                {
-                                                    let v = voice.to_string();
-                                                    ui.selectable_value(
-                                                        &mut self.tts_voice_draft,
-                                                        v.clone(),
-                                                        v,
-                                                    );
-                                                }
-                                            });
-                                    });
-                                    ui.horizontal(|ui| {
-                                        ui.label("HTTP Port");
-                                        ui.add(
-                                            egui::DragValue::new(&mut self.server_port_draft)
-                                                .range(1025..=65535),
-                                        );
-                                    });
-                                    ui.checkbox(
-                                        &mut self.show_endpoint_text_draft,
-                                        "Show endpoint text in UI",
-                                    );
-                                    ui.horizontal(|ui| {
-                                        ui.label("xAI Delivery Style");
-                                        ui.add_enabled_ui(
-                                            self.tts_provider_draft == Provider::Xai,
-                                            |ui| {
-                                                ui.text_edit_singleline(&mut self.xai_style_draft);
-                                            },
-                                        );
-                                    });
-                                    if self.tts_provider_draft != Provider::Xai {
-                                        ui.small(
-                                            "Only xAI realtime currently supports style prompting.",
-                                        );
-                                    }
-                                });
-
-                            if ui.button("Save Configuration").clicked() {
+                                .open(search.subsection("Text To Speech + Voice Bridge"))
+                                .show(ui, |ui| self.tts_settings(ui, &search));
+
+                            search.leave_subsection();
+                            if self.general_settings(ui, &search) {
                                 save_main = true;
                             }
                         });
@@ -360,64 +1979,20 @@ impl eframe::App for Push2TypeApp {
                     egui::CollapsingHeader::new("Tools")
                         .id_salt("section_tools")
                         .default_open(false)
-                        .show(ui, |ui| {
-                            ui.horizontal(|ui| {
-                                if ui.button("Manual Capture + STT").clicked() {
-                                    let recorder = self.recorder.clone();
-                                    let stt_tx = self.stt_tx.clone();
-                                    std::thread::spawn(move || {
-                                        recorder.start_capture();
-                                        std::thread::sleep(Duration::from_millis(1300));
-                                        let audio = recorder.stop_capture();
-                                        if !audio.is_empty() {
-                                            let _ = stt_tx.send(audio);
-                                        }
-                                    });
-                                }
-                                ui.label("Records ~1.3s then transcribes.");
-                            });
-                            ui.separator();
-                            ui.label("Voice test:");
-                            ui.horizontal(|ui| {
-                                ui.label("Persona");
-                                ui.text_edit_singleline(&mut self.persona_input);
-                            });
-                            ui.text_edit_singleline(&mut self.message_input);
-                            if ui.button("Speak Test").clicked() {
-                                let req = TtsRequest {
-                                    speak: SpeakRequest {
-                                        message: self.message_input.clone(),
-                                        persona: Some(self.persona_input.clone()),
-                                        voice: Some(self.tts_voice_draft.clone()),
-                                        provider: Some(self.tts_provider_draft),
-                                        show_text: Some(true),
-                                        style: Some(self.xai_style_draft.clone()),
-                                    },
-                                };
-                                let _ = self.tts_tx.send(req);
-                            }
-                        });
+                        .open(search.section("Tools"))
+                        .show(ui, |ui| self.tools_section(ui, &search));
 
                     egui::CollapsingHeader::new("Logs")
                         .id_salt("section_logs")
                         .default_open(false)
-                        .show(ui, |ui| {
-                            ui.label(format!("Last Spoken: {}", self.last_spoken));
-                            egui::ScrollArea::vertical()
-                                .auto_shrink([false, false])
-                                .max_height(140.0)
-                                .show(ui, |ui| {
-                                    for line in self.logs.iter().rev().take(80) {
-                                        ui.monospace(line);
-                                    }
-                                });
-                        });
+                        .open(search.section("Logs"))
+                        .show(ui, |ui| self.logs_section(ui));
                 });
 
-            if let Some((status, when)) = &self.last_save_status {
-                if when.elapsed() < Duration::from_secs(4) {
-                    ui.label(status);
-                }
+            if let Some((status, when)) = &self.last_save_status
+                && when.elapsed() < Duration::from_secs(4)
+            {
+                ui.label(status);
             }
             // Measure only used content height; min_rect can track available panel size and cause growth loops.
             let used_height = ui.cursor().min.y - ui.min_rect().top();
@@ -425,8 +2000,16 @@ impl eframe::App for Push2TypeApp {
         });
 
         let current_width = ctx.input(|i| i.screen_rect().width()).max(420.0);
-        let target_height = (content_height + 40.0).clamp(240.0, 900.0);
-        if (target_height - self.last_applied_height).abs() > 6.0 {
+        if self.auto_resize_draft
+            && let Some(target_height) = next_window_height(
+                content_height,
+                self.last_applied_height,
+                self.previous_applied_height,
+                self.last_resize_at.elapsed() < RESIZE_SETTLE_WINDOW,
+            )
+        {
+            self.last_resize_at = Instant::now();
+            self.previous_applied_height = self.last_applied_height;
             self.last_applied_height = target_height;
             ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
                 current_width,
@@ -435,21 +2018,131 @@ impl eframe::App for Push2TypeApp {
         }
 
         if save_main {
-            let runtime_port = self.server_port_draft;
-            let runtime_enabled = self.tts_bridge_enabled_draft;
-            let mut cfg = self.config.lock().expect("config lock");
+            let mut shortcut_warning = None;
+            let mut cfg = config::lock(&self.config);
             cfg.hotkey = self.hotkey_draft.clone();
-            cfg.server_port = runtime_port;
-            cfg.tts_bridge_enabled = runtime_enabled;
+            cfg.cancel_hotkey = self.cancel_hotkey_draft.trim().to_string();
+            cfg.hotkey_mode = self.hotkey_mode_draft;
+            cfg.double_tap_ms = self.double_tap_ms_draft;
+            cfg.min_capture_ms = self.min_capture_ms_draft;
+            cfg.capture_cooldown_ms = self.capture_cooldown_ms_draft;
+            cfg.release_grace_ms = self.release_grace_ms_draft;
+            cfg.cue_sounds = self.cue_sounds_draft;
+            cfg.server_port = self.server_port_draft;
+            cfg.tts_bridge_enabled = self.tts_bridge_enabled_draft;
+            cfg.inject_enabled = self.inject_enabled_draft;
             cfg.show_endpoint_text = self.show_endpoint_text_draft;
+            cfg.server_auth_token = self.server_auth_token_draft.trim().to_string();
+            cfg.speak_rate_per_min = self.speak_rate_per_min_draft;
+            cfg.log_to_file = self.log_to_file_draft;
+            cfg.mock_mode = self.mock_mode_draft;
+            cfg.log_level = self.log_level_draft;
+            cfg.log_max_lines = self.log_max_lines_draft;
+            cfg.theme = self.theme_draft;
+            cfg.listen_accent = self.listen_accent_draft;
+            cfg.always_on_top = self.always_on_top_draft;
+            cfg.auto_resize = self.auto_resize_draft;
+            cfg.input_gain = self.input_gain_draft;
+            cfg.monitor_volume = self.monitor_volume_draft;
+            cfg.downmix_mode = self.downmix_mode_draft;
+            cfg.preroll_ms = self.preroll_ms_draft.min(audio::MAX_PREROLL_MS);
+            self.recorder.set_preroll(cfg.preroll_ms);
+            cfg.noise_gate_enabled = self.noise_gate_enabled_draft;
+            cfg.tts_normalize = self.tts_normalize_draft;
+            cfg.tts_target_peak = self.tts_target_peak_draft;
+            cfg.tts_keep_output_open = self.tts_keep_output_open_draft;
+            cfg.duck_tts_on_listen = self.duck_tts_on_listen_draft;
+            cfg.noise_gate_threshold = self.noise_gate_threshold_draft;
+            cfg.vad_enabled = self.vad_enabled_draft;
+            cfg.vad_silence_ms = self.vad_silence_ms_draft;
+            cfg.vad_threshold = self.vad_threshold_draft;
             cfg.stt_language = self.stt_language_draft.clone();
+            cfg.stt_prompt = self.stt_prompt_draft.clone();
+            cfg.stt_min_confidence = self.stt_min_confidence_draft;
+            cfg.stt_streaming = self.stt_streaming_draft;
+            cfg.stt_upload_format = self.stt_upload_format_draft;
+            cfg.stt_coalesce = self.stt_coalesce_draft;
+            cfg.inject_suffix = self.inject_suffix_draft.clone();
+            cfg.inject_command = Some(self.inject_command_draft.trim().to_string())
+                .filter(|command| !command.is_empty());
+            cfg.inject_settle_ms = self.inject_settle_ms_draft;
+            cfg.inject_focus_stable_ms = self.inject_focus_stable_ms_draft;
+            cfg.inject_focus_timeout_ms = self.inject_focus_timeout_ms_draft;
+            cfg.http_timeout_secs = self.http_timeout_secs_draft;
+            cfg.inject_verify = self.inject_verify_draft;
+            cfg.inject_submit = self.inject_submit_draft;
+            cfg.inject_placement = self.inject_placement_draft;
+            cfg.auto_capitalize = self.auto_capitalize_draft;
+            cfg.auto_period = self.auto_period_draft;
+            cfg.speak_transcript_back = self.speak_transcript_back_draft;
+            cfg.inject_allowlist = self
+                .inject_allowlist_draft
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            if inject::parse_paste_shortcut(&self.paste_shortcut_draft).is_ok() {
+                cfg.paste_shortcut = self.paste_shortcut_draft.trim().to_lowercase();
+            } else {
+                shortcut_warning = Some(format!(
+                    "WARN: paste shortcut '{}' invalid, keeping '{}'",
+                    self.paste_shortcut_draft, cfg.paste_shortcut
+                ));
+            }
+            cfg.hotkey_bindings = self
+                .hotkey_bindings_draft
+                .iter()
+                .filter(|b| !b.hotkey.trim().is_empty())
+                .cloned()
+                .collect();
+            cfg.voice_aliases = self
+                .voice_aliases_draft
+                .iter()
+                .filter(|(name, alias)| !name.trim().is_empty() && !alias.voice.trim().is_empty())
+                .map(|(name, alias)| {
+                    let alias = VoiceAlias {
+                        provider: alias.provider,
+                        voice: alias.voice.trim().to_string(),
+                    };
+                    (name.trim().to_string(), alias)
+                })
+                .collect();
+            cfg.replacements = self
+                .replacements_draft
+                .iter()
+                .filter(|r| !r.find.trim().is_empty())
+                .cloned()
+                .collect();
+            cfg.strip_prefixes = self
+                .strip_prefixes_draft
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
             self.stt_model_by_provider_draft.insert(
                 provider_label(self.stt_provider_draft).to_string(),
                 self.stt_model_draft.clone(),
             );
             cfg.stt_model_by_provider = self.stt_model_by_provider_draft.clone();
+            self.stt_language_by_provider_draft.insert(
+                provider_label(self.stt_provider_draft).to_string(),
+                self.stt_language_draft.clone(),
+            );
+            cfg.stt_language_by_provider = self.stt_language_by_provider_draft.clone();
+            cfg.stt_base_urls = base_urls(&self.stt_base_urls_draft);
+            cfg.tts_base_urls = base_urls(&self.tts_base_urls_draft);
             cfg.set_stt_model_for(self.stt_provider_draft, self.stt_model_draft.clone());
             cfg.stt_provider = self.stt_provider_draft;
+            cfg.stt_backend = if self.local_whisper_draft {
+                SttBackend::Local
+            } else {
+                SttBackend::Hosted
+            };
+            cfg.local_whisper_model = self.local_whisper_model_draft.trim().to_string();
+            cfg.save_recordings_dir = Some(self.save_recordings_dir_draft.trim().to_string())
+                .filter(|dir| !dir.is_empty());
             cfg.tts_provider = self.tts_provider_draft;
             self.tts_voice_by_provider_draft.insert(
                 provider_label(self.tts_provider_draft).to_string(),
@@ -470,30 +2163,339 @@ impl eframe::App for Push2TypeApp {
                 .get("groq")
                 .cloned()
                 .unwrap_or_else(|| cfg.groq_voice.clone());
+            cfg.elevenlabs_voice = self
+                .tts_voice_by_provider_draft
+                .get("elevenlabs")
+                .map(|voice| voice.trim().to_string())
+                .unwrap_or_else(|| cfg.elevenlabs_voice.clone());
             cfg.xai_tts_style = self.xai_style_draft.clone();
+            cfg.tts_verbatim = self.tts_verbatim_draft;
+            // An emptied box keeps the previous model rather than sending "" to the provider.
+            let models = &mut *cfg;
+            for (draft, target) in [
+                (
+                    &self.xai_realtime_model_draft,
+                    &mut models.xai_realtime_model,
+                ),
+                (&self.openai_tts_model_draft, &mut models.openai_tts_model),
+                (&self.groq_tts_model_draft, &mut models.groq_tts_model),
+                (
+                    &self.elevenlabs_tts_model_draft,
+                    &mut models.elevenlabs_tts_model,
+                ),
+            ] {
+                if !draft.trim().is_empty() {
+                    *target = draft.trim().to_string();
+                }
+            }
             let save_res = cfg.save();
             self.last_save_status = Some(match save_res {
                 Ok(_) => ("Saved config.".to_string(), Instant::now()),
                 Err(e) => (format!("Save failed: {e}"), Instant::now()),
             });
             drop(cfg);
-            self.server_control.set_port(runtime_port);
-            self.server_control.set_enabled(runtime_enabled);
-            if !runtime_enabled {
-                self.endpoint = "Disabled".to_string();
+            if let Some(warning) = shortcut_warning {
+                self.push_log(LogLevel::Warn, warning);
+            }
+            self.hotkey_control.reload();
+            self.apply_server_settings();
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some((geometry, _)) = self.pending_geometry {
+            self.save_window_geometry(geometry);
+        }
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        self.server_control.shutdown(SERVER_STOP_TIMEOUT);
+        // Let the current utterance and transcription finish so saved recordings aren't cut off.
+        while (self.stt_busy || self.tts_busy) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(50));
+            self.drain_events();
+        }
+    }
+}
+
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+const SERVER_STOP_TIMEOUT: Duration = Duration::from_secs(1);
+
+// x, y, width and (only with auto-resize off) height, in logical points.
+type WindowGeometry = (f32, f32, f32, Option<f32>);
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+const MIN_WINDOW_HEIGHT: f32 = 240.0;
+const MAX_WINDOW_HEIGHT: f32 = 900.0;
+const HEIGHT_DEADBAND: f32 = 6.0;
+const RESIZE_SETTLE_WINDOW: Duration = Duration::from_millis(400);
+const LISTEN_BORDER_WIDTH: f32 = 3.0;
+
+// Returns the height to resize to, or None to leave the window alone. Targets are
+// rounded and clamped, small changes are ignored, and a quick bounce back to the
+// height we just left (layout reacting to the resize) settles on the taller of the two.
+fn next_window_height(
+    content_height: f32,
+    applied: f32,
+    previous: f32,
+    just_resized: bool,
+) -> Option<f32> {
+    let target = (content_height + 40.0)
+        .round()
+        .clamp(MIN_WINDOW_HEIGHT, MAX_WINDOW_HEIGHT);
+    if (target - applied).abs() <= HEIGHT_DEADBAND {
+        return None;
+    }
+    if just_resized && (target - previous).abs() <= HEIGHT_DEADBAND {
+        let settled = target.max(applied);
+        return ((settled - applied).abs() > HEIGHT_DEADBAND).then_some(settled);
+    }
+    Some(target)
+}
+
+fn default_settings_export_path() -> String {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join("push2type_settings.json")
+        .display()
+        .to_string()
+}
+
+fn missing_key_indicator(ui: &mut egui::Ui, provider: Provider) {
+    if !config::has_api_key(provider) {
+        ui.colored_label(
+            egui::Color32::RED,
+            format!("{} missing", config::api_key_env(provider)),
+        );
+    }
+}
+
+// The Advanced search box. Rows added through `row` and `checkbox` match on their own
+// label plus the titles of the sections around them, so a new setting is searchable as
+// soon as it is added; list entries pass their contents to `shows`. Which sections hold a match is only known once their rows have
+// been laid out, so a new search opens everything for one frame and, from the next
+// frame on, keeps open only the sections that had a hit.
+#[derive(Default)]
+struct SettingsSearch {
+    words: RefCell<Vec<String>>,
+    fresh: Cell<bool>,
+    hits: RefCell<HashMap<&'static str, usize>>,
+    counting: RefCell<HashMap<&'static str, usize>>,
+    group: Cell<Option<&'static str>>,
+    current: Cell<Option<&'static str>>,
+}
+
+impl SettingsSearch {
+    // True when the search changed, so the caller should ask for the frame that settles it.
+    fn begin_frame(&self, filter: &str) -> bool {
+        let words: Vec<String> = filter
+            .to_lowercase()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+        let changed = *self.words.borrow() != words;
+        let mut counting = self.counting.borrow_mut();
+        if changed {
+            *self.words.borrow_mut() = words;
+            self.hits.borrow_mut().clear();
+            counting.clear();
+        } else {
+            *self.hits.borrow_mut() = std::mem::take(&mut *counting);
+        }
+        self.fresh.set(changed);
+        self.group.set(None);
+        self.current.set(None);
+        changed
+    }
+
+    // `.open()` values for a section that only holds subsections, one of those
+    // subsections, and a section on its own.
+    fn group(&self, title: &'static str) -> Option<bool> {
+        self.group.set(Some(title));
+        self.subsection(title)
+    }
+
+    fn subsection(&self, title: &'static str) -> Option<bool> {
+        self.current.set(Some(title));
+        if self.words.borrow().is_empty() {
+            return None;
+        }
+        // A title match alone opens the section, for ones that only hold lists.
+        let title_lower = title.to_lowercase();
+        if self
+            .words
+            .borrow()
+            .iter()
+            .all(|word| title_lower.contains(word.as_str()))
+        {
+            let mut counting = self.counting.borrow_mut();
+            for title in [Some(title), self.group.get().filter(|g| *g != title)]
+                .into_iter()
+                .flatten()
+            {
+                *counting.entry(title).or_default() += 1;
             }
         }
+        Some(self.fresh.get() || self.hits.borrow().get(title).is_some_and(|&n| n > 0))
+    }
+
+    fn section(&self, title: &'static str) -> Option<bool> {
+        self.group.set(None);
+        self.subsection(title)
+    }
+
+    // For rows that follow the last subsection directly inside the group.
+    fn leave_subsection(&self) {
+        self.current.set(self.group.get());
+    }
+
+    fn shows(&self, label: &str) -> bool {
+        let words = self.words.borrow();
+        if words.is_empty() {
+            return true;
+        }
+        let (current, group) = (self.current.get(), self.group.get());
+        let haystack = format!(
+            "{label} {} {}",
+            current.unwrap_or_default(),
+            group.unwrap_or_default()
+        )
+        .to_lowercase();
+        if !words.iter().all(|word| haystack.contains(word.as_str())) {
+            return false;
+        }
+        let mut counting = self.counting.borrow_mut();
+        for title in [current, group.filter(|g| Some(*g) != current)]
+            .into_iter()
+            .flatten()
+        {
+            *counting.entry(title).or_default() += 1;
+        }
+        true
+    }
+
+    fn row(&self, ui: &mut egui::Ui, label: &str, add_contents: impl FnOnce(&mut egui::Ui)) {
+        if self.shows(label) {
+            ui.horizontal(|ui| {
+                ui.label(label);
+                add_contents(ui);
+            });
+        }
+    }
+
+    // A hidden checkbox hands back a response that takes no space, so `.changed()` and
+    // hover text still chain as usual.
+    fn checkbox(&self, ui: &mut egui::Ui, checked: &mut bool, label: &str) -> egui::Response {
+        if self.shows(label) {
+            ui.checkbox(checked, label)
+        } else {
+            ui.interact(
+                egui::Rect::NOTHING,
+                ui.id().with(label),
+                egui::Sense::hover(),
+            )
+        }
     }
 }
 
+fn base_url_field(ui: &mut egui::Ui, urls: &mut HashMap<String, String>, provider: Provider) {
+    let key = provider_label(provider);
+    ui.horizontal(|ui| {
+        ui.label(key);
+        ui.add(
+            egui::TextEdit::singleline(urls.entry(key.to_string()).or_default())
+                .hint_text(config::default_base_url(provider)),
+        );
+    });
+}
+
+fn base_urls(drafts: &HashMap<String, String>) -> HashMap<String, String> {
+    drafts
+        .iter()
+        .map(|(provider, url)| (provider.clone(), url.trim().to_string()))
+        .filter(|(_, url)| !url.is_empty())
+        .collect()
+}
+
 fn provider_label(provider: Provider) -> &'static str {
     match provider {
         Provider::Groq => "groq",
+        Provider::ElevenLabs => "elevenlabs",
         Provider::OpenAi => "openai",
         Provider::Xai => "xai",
     }
 }
 
+const STT_LANGUAGES: &[(&str, &str)] = &[
+    ("auto", "Auto"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("ru", "Russian"),
+    ("uk", "Ukrainian"),
+    ("tr", "Turkish"),
+    ("ar", "Arabic"),
+    ("hi", "Hindi"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("zh", "Chinese"),
+];
+
+fn stt_language_label(code: &str) -> String {
+    if code.trim().is_empty() {
+        return "Auto".to_string();
+    }
+    STT_LANGUAGES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(c, name)| {
+            if *c == "auto" {
+                name.to_string()
+            } else {
+                format!("{name} ({c})")
+            }
+        })
+        .unwrap_or_else(|| code.to_string())
+}
+
+const PASTE_SHORTCUTS: &[&str] = &["ctrl+v", "cmd+v", "ctrl+shift+v", "shift+insert"];
+
+const RESET_CONFIRM_WINDOW: Duration = Duration::from_secs(4);
+
+const STT_TEST_CAPTURE: Duration = Duration::from_secs(3);
+
+const INJECT_SUFFIXES: &[(&str, &str)] = &[("", "None"), (" ", "Space"), ("\n", "Newline")];
+
+fn inject_suffix_label(suffix: &str) -> String {
+    INJECT_SUFFIXES
+        .iter()
+        .find(|(s, _)| *s == suffix)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| format!("{suffix:?}"))
+}
+
+// Suggestions only; the model box accepts any id so newer models work without an update.
+fn tts_models_for_provider(provider: Provider) -> Vec<&'static str> {
+    match provider {
+        Provider::Xai => vec!["grok-4-voice"],
+        Provider::OpenAi => vec![
+            "gpt-4o-mini-tts-2025-12-15",
+            "gpt-4o-mini-tts",
+            "tts-1",
+            "tts-1-hd",
+        ],
+        Provider::Groq => vec!["canopylabs/orpheus-v1-english"],
+        Provider::ElevenLabs => vec![
+            "eleven_flash_v2_5",
+            "eleven_turbo_v2_5",
+            "eleven_multilingual_v2",
+        ],
+    }
+}
+
 fn tts_voices_for_provider(provider: Provider) -> Vec<&'static str> {
     match provider {
         Provider::Xai => vec!["ara", "rex", "sal", "eve", "leo"],
@@ -502,5 +2504,6 @@ fn tts_voices_for_provider(provider: Provider) -> Vec<&'static str> {
             "verse", "marin", "cedar",
         ],
         Provider::Groq => vec!["autumn", "diana", "hannah", "austin", "daniel", "troy"],
+        Provider::ElevenLabs => Vec::new(),
     }
 }
@@ -1,124 +1,392 @@
-use std::sync::{
-    Arc, Mutex,
-    atomic::{AtomicBool, Ordering},
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicUsize, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context, anyhow};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use crossbeam_channel::{Sender, bounded};
 
-use crate::app::AppEvent;
+use crate::{app::AppEvent, config::DownmixMode, dsp};
 
 pub struct AudioRecorder {
     sample_rate: u32,
-    capturing: Arc<AtomicBool>,
-    buffer: Arc<Mutex<Vec<i16>>>,
-    _stream: cpal::Stream,
+    capture: Arc<CaptureBuffer>,
+    events: Sender<AppEvent>,
+    started_at: Mutex<Option<Instant>>,
+    // Samples of capacity to have in the buffer before each capture starts.
+    reserve_len: AtomicUsize,
+    // f32 bits, read by the monitor thread for every block it plays.
+    monitor_volume: Arc<AtomicU32>,
+    // Set while TTS is speaking, so the monitor doesn't replay it through the mic.
+    monitor_paused: Arc<AtomicBool>,
+    _stream: Arc<Mutex<Option<cpal::Stream>>>,
 }
 
-impl AudioRecorder {
-    pub fn new(events: crossbeam_channel::Sender<AppEvent>) -> anyhow::Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .context("no default input device available")?;
-        let supported = device.default_input_config()?;
-        let sample_rate = supported.sample_rate().0;
-        let channels = supported.channels() as usize;
-        let sample_format = supported.sample_format();
-        let config: cpal::StreamConfig = supported.into();
-
-        let capturing = Arc::new(AtomicBool::new(false));
-        let buffer = Arc::new(Mutex::new(Vec::<i16>::new()));
-        let capturing_clone = capturing.clone();
-        let buffer_clone = buffer.clone();
-        let err_events = events.clone();
-
-        let stream = match sample_format {
-            cpal::SampleFormat::I16 => device.build_input_stream(
-                &config,
-                move |data: &[i16], _| {
-                    if capturing_clone.load(Ordering::Relaxed) {
-                        let mono = downmix_i16_to_mono(data, channels);
-                        if let Ok(mut buf) = buffer_clone.lock() {
-                            buf.extend_from_slice(&mono);
-                        }
-                    }
-                },
-                move |err| {
-                    let _ = err_events.send(AppEvent::Error(format!("audio error: {err}")));
-                },
-                None,
-            )?,
-            cpal::SampleFormat::U16 => {
-                let capturing_clone = capturing.clone();
-                let buffer_clone = buffer.clone();
-                device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _| {
-                        let converted: Vec<i16> = data
-                            .iter()
-                            .map(|s| {
-                                (*s as i32 - 32768).clamp(i16::MIN as i32, i16::MAX as i32) as i16
-                            })
-                            .collect();
-                        if capturing_clone.load(Ordering::Relaxed) {
-                            let mono = downmix_i16_to_mono(&converted, channels);
-                            if let Ok(mut buf) = buffer_clone.lock() {
-                                buf.extend_from_slice(&mono);
-                            }
-                        }
-                    },
-                    move |err| {
-                        let _ = events.send(AppEvent::Error(format!("audio error: {err}")));
-                    },
-                    None,
-                )?
-            }
-            cpal::SampleFormat::F32 => {
-                let capturing_clone = capturing.clone();
-                let buffer_clone = buffer.clone();
-                device.build_input_stream(
-                    &config,
-                    move |data: &[f32], _| {
-                        let converted: Vec<i16> = data
-                            .iter()
-                            .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
-                            .collect();
-                        if capturing_clone.load(Ordering::Relaxed) {
-                            let mono = downmix_i16_to_mono(&converted, channels);
-                            if let Ok(mut buf) = buffer_clone.lock() {
-                                buf.extend_from_slice(&mono);
-                            }
-                        }
-                    },
-                    move |err| {
-                        let _ = events.send(AppEvent::Error(format!("audio error: {err}")));
-                    },
-                    None,
-                )?
-            }
-            _ => return Err(anyhow!("unsupported sample format")),
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+// Samples this close to full scale count as clipped; warn past 1% of a capture.
+const CLIP_LEVEL: u16 = 32_000;
+const CLIP_WARN_FRACTION: f32 = 0.01;
+// A capture this long with no input callbacks at all means the device isn't delivering;
+// shorter taps can legitimately end before the first block arrives.
+const NO_AUDIO_AFTER: Duration = Duration::from_millis(250);
+pub const MIN_INPUT_GAIN: f32 = 0.5;
+pub const MAX_INPUT_GAIN: f32 = 4.0;
+pub const MAX_PREROLL_MS: u64 = 2000;
+const MAX_RESERVE_SECS: u32 = 600;
+pub const MAX_MONITOR_VOLUME: f32 = 1.0;
+// Input blocks waiting for the monitor thread; beyond this they are dropped.
+const MONITOR_BACKLOG: usize = 16;
+// Blocks queued in the output sink before the monitor skips audio to stay near live.
+const MONITOR_MAX_QUEUED: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VadSettings {
+    pub silence_ms: u64,
+    pub threshold: u16,
+}
+
+// State shared between the cpal callback and the recorder handle.
+struct CaptureBuffer {
+    capturing: AtomicBool,
+    // Off for test captures, whose audio must never reach the dictation VAD sink.
+    split: AtomicBool,
+    buffer: Mutex<Vec<i16>>,
+    // Rolling copy of the last `preroll_len` samples heard while idle, so a capture can
+    // start slightly before the hotkey went down.
+    preroll: Mutex<VecDeque<i16>>,
+    preroll_len: AtomicUsize,
+    vad: Mutex<Option<VadSegmenter>>,
+    // f32 bits; read on the audio thread without taking a lock.
+    gain: AtomicU32,
+    // Peak of the latest callback block after gain, for the UI level meter.
+    peak: AtomicU16,
+    // DownmixMode::channel(); 0 averages every channel.
+    downmix_channel: AtomicU16,
+    // Per-capture totals for the clipping warning.
+    captured_samples: AtomicUsize,
+    clipped_samples: AtomicUsize,
+    // Copy of every block for "Monitor Mic"; None while monitoring is off.
+    monitor: Mutex<Option<Sender<Vec<i16>>>>,
+}
+
+impl CaptureBuffer {
+    fn push(&self, mono: &[i16]) {
+        let gain = f32::from_bits(self.gain.load(Ordering::Relaxed));
+        let boosted: Vec<i16>;
+        let mono = if gain == 1.0 {
+            mono
+        } else {
+            boosted = mono
+                .iter()
+                .map(|s| (*s as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16)
+                .collect();
+            &boosted
         };
-        stream.play()?;
+        let peak = mono.iter().map(|s| s.unsigned_abs()).max().unwrap_or(0);
+        self.peak.store(peak, Ordering::Relaxed);
+        if let Ok(monitor) = self.monitor.lock()
+            && let Some(tx) = monitor.as_ref()
+        {
+            let _ = tx.try_send(mono.to_vec());
+        }
+        if !self.capturing.load(Ordering::Relaxed) {
+            self.keep_preroll(mono);
+            return;
+        }
+        let clipped = mono
+            .iter()
+            .filter(|s| s.unsigned_abs() >= CLIP_LEVEL)
+            .count();
+        self.captured_samples
+            .fetch_add(mono.len(), Ordering::Relaxed);
+        self.clipped_samples.fetch_add(clipped, Ordering::Relaxed);
+        let Ok(mut buf) = self.buffer.lock() else {
+            return;
+        };
+        buf.extend_from_slice(mono);
+        if self.split.load(Ordering::Relaxed)
+            && let Ok(mut vad) = self.vad.lock()
+            && let Some(segmenter) = vad.as_mut()
+            && segmenter.observe(mono)
+        {
+            // Copy the segment out so the reserved buffer stays in place for the rest.
+            let segment = buf.to_vec();
+            buf.clear();
+            let _ = segmenter.sink.send(segment);
+        }
+    }
+
+    fn keep_preroll(&self, mono: &[i16]) {
+        let len = self.preroll_len.load(Ordering::Relaxed);
+        let Ok(mut ring) = self.preroll.lock() else {
+            return;
+        };
+        ring.extend(mono.iter().rev().take(len).rev());
+        let excess = ring.len().saturating_sub(len);
+        ring.drain(..excess);
+    }
+}
+
+// Energy-based VAD: once speech has been heard, a silence gap longer than
+// `silence_ms` closes the current segment so STT can start on it.
+struct VadSegmenter {
+    silence_samples: usize,
+    threshold: f32,
+    sink: Sender<Vec<i16>>,
+    speech_seen: bool,
+    silent_run: usize,
+}
+
+impl VadSegmenter {
+    fn observe(&mut self, chunk: &[i16]) -> bool {
+        if chunk.is_empty() {
+            return false;
+        }
+        let sum_sq: f64 = chunk.iter().map(|s| (*s as f64) * (*s as f64)).sum();
+        let rms = (sum_sq / chunk.len() as f64).sqrt() as f32;
+        if rms >= self.threshold {
+            self.speech_seen = true;
+            self.silent_run = 0;
+            return false;
+        }
+        self.silent_run += chunk.len();
+        if self.speech_seen && self.silent_run >= self.silence_samples {
+            self.speech_seen = false;
+            self.silent_run = 0;
+            return true;
+        }
+        false
+    }
+
+    fn reset(&mut self) {
+        self.speech_seen = false;
+        self.silent_run = 0;
+    }
+}
+
+impl AudioRecorder {
+    pub fn new(events: Sender<AppEvent>) -> anyhow::Result<Self> {
+        let capture = Arc::new(CaptureBuffer {
+            capturing: AtomicBool::new(false),
+            split: AtomicBool::new(true),
+            buffer: Mutex::new(Vec::new()),
+            preroll: Mutex::new(VecDeque::new()),
+            preroll_len: AtomicUsize::new(0),
+            vad: Mutex::new(None),
+            gain: AtomicU32::new(1.0f32.to_bits()),
+            peak: AtomicU16::new(0),
+            downmix_channel: AtomicU16::new(0),
+            captured_samples: AtomicUsize::new(0),
+            clipped_samples: AtomicUsize::new(0),
+            monitor: Mutex::new(None),
+        });
+        let degraded = Arc::new(AtomicBool::new(false));
+        let (stream, sample_rate) = open_input_stream(None, &capture, &degraded, &events)?;
+        let stream = Arc::new(Mutex::new(Some(stream)));
+        spawn_reconnect_watchdog(
+            sample_rate,
+            capture.clone(),
+            stream.clone(),
+            degraded,
+            events.clone(),
+        );
 
         Ok(Self {
             sample_rate,
-            capturing,
-            buffer,
+            capture,
+            events,
+            started_at: Mutex::new(None),
+            reserve_len: AtomicUsize::new(0),
+            monitor_volume: Arc::new(AtomicU32::new(0.3f32.to_bits())),
+            monitor_paused: Arc::new(AtomicBool::new(false)),
             _stream: stream,
         })
     }
 
     pub fn start_capture(&self) {
-        if let Ok(mut buf) = self.buffer.lock() {
+        self.begin_capture(true);
+    }
+
+    // For Tools and diagnostics: the whole clip comes back from `stop_capture`, and
+    // VAD segmentation stays off so nothing is dictated and pasted mid-capture.
+    pub fn start_test_capture(&self) {
+        self.begin_capture(false);
+    }
+
+    fn begin_capture(&self, split: bool) {
+        if let Ok(mut buf) = self.capture.buffer.lock() {
             buf.clear();
+            buf.reserve(self.reserve_len.load(Ordering::Relaxed));
+            if let Ok(mut ring) = self.capture.preroll.lock() {
+                buf.extend(ring.drain(..));
+            }
         }
-        self.capturing.store(true, Ordering::Relaxed);
+        if let Ok(mut vad) = self.capture.vad.lock()
+            && let Some(segmenter) = vad.as_mut()
+        {
+            segmenter.reset();
+        }
+        if let Ok(mut started_at) = self.started_at.lock() {
+            *started_at = Some(Instant::now());
+        }
+        self.capture.captured_samples.store(0, Ordering::Relaxed);
+        self.capture.clipped_samples.store(0, Ordering::Relaxed);
+        self.capture.split.store(split, Ordering::Relaxed);
+        self.capture.capturing.store(true, Ordering::Relaxed);
+    }
+
+    // Wall-clock time since the current (or last) capture started.
+    pub fn capture_elapsed(&self) -> Duration {
+        self.started_at
+            .lock()
+            .ok()
+            .and_then(|started_at| started_at.map(|at| at.elapsed()))
+            .unwrap_or_default()
     }
 
+    // With VAD on, earlier segments have already gone to the sink; this returns the tail.
     pub fn stop_capture(&self) -> Vec<i16> {
-        self.capturing.store(false, Ordering::Relaxed);
-        self.buffer.lock().map(|b| b.clone()).unwrap_or_default()
+        self.capture.capturing.store(false, Ordering::Relaxed);
+        let captured = self.capture.captured_samples.load(Ordering::Relaxed);
+        let clipped = self.capture.clipped_samples.load(Ordering::Relaxed);
+        // Every caller (hotkeys, the UI button, the Tools captures) ends up here, so a
+        // muted or stalled input is reported once for all of them.
+        if captured == 0 && self.capture_elapsed() >= NO_AUDIO_AFTER {
+            let _ = self.events.send(AppEvent::Warning(
+                "no audio captured \u{2014} check mic".to_string(),
+            ));
+        }
+        if captured > 0 && clipped as f32 / captured as f32 > CLIP_WARN_FRACTION {
+            let _ = self.events.send(AppEvent::Warning(format!(
+                "input clipping detected ({:.1}% of samples); lower gain",
+                clipped as f32 * 100.0 / captured as f32
+            )));
+        }
+        // Copied out rather than taken, so the next capture reuses this allocation.
+        let tail = self
+            .capture
+            .buffer
+            .lock()
+            .map(|mut b| {
+                let tail = b.to_vec();
+                b.clear();
+                tail
+            })
+            .unwrap_or_default();
+        // A tail with no speech after the last VAD cut is just trailing silence.
+        let silent_tail = self.capture.split.load(Ordering::Relaxed)
+            && self
+                .capture
+                .vad
+                .lock()
+                .is_ok_and(|vad| vad.as_ref().is_some_and(|s| !s.speech_seen));
+        if silent_tail { Vec::new() } else { tail }
+    }
+
+    pub fn abort_capture(&self) {
+        self.capture.capturing.store(false, Ordering::Relaxed);
+        if let Ok(mut buf) = self.capture.buffer.lock() {
+            buf.clear();
+        }
+    }
+
+    pub fn set_vad(&self, settings: Option<VadSettings>, sink: &Sender<Vec<i16>>) {
+        let segmenter = settings.map(|s| VadSegmenter {
+            silence_samples: (self.sample_rate as u64 * s.silence_ms / 1000) as usize,
+            threshold: s.threshold as f32,
+            sink: sink.clone(),
+            speech_seen: false,
+            silent_run: 0,
+        });
+        if let Ok(mut vad) = self.capture.vad.lock() {
+            *vad = segmenter;
+        }
+    }
+
+    pub fn set_downmix(&self, mode: DownmixMode) {
+        self.capture
+            .downmix_channel
+            .store(mode.channel(), Ordering::Relaxed);
+    }
+
+    pub fn set_capture_reserve(&self, secs: u32) {
+        let len = self.sample_rate as usize * secs.min(MAX_RESERVE_SECS) as usize;
+        self.reserve_len.store(len, Ordering::Relaxed);
+    }
+
+    pub fn set_preroll(&self, ms: u64) {
+        let len = (self.sample_rate as u64 * ms.min(MAX_PREROLL_MS) / 1000) as usize;
+        self.capture.preroll_len.store(len, Ordering::Relaxed);
+        if let Ok(mut ring) = self.capture.preroll.lock() {
+            let excess = ring.len().saturating_sub(len);
+            ring.drain(..excess);
+        }
+    }
+
+    pub fn set_input_gain(&self, gain: f32) {
+        let gain = gain.clamp(MIN_INPUT_GAIN, MAX_INPUT_GAIN);
+        self.capture.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
+    // Plays the live input on the default output until `stop_monitor`. rodio's
+    // OutputStream isn't Send, so it is opened and kept on the monitor's own thread.
+    pub fn start_monitor(&self) {
+        let (tx, rx) = bounded::<Vec<i16>>(MONITOR_BACKLOG);
+        if let Ok(mut monitor) = self.capture.monitor.lock() {
+            *monitor = Some(tx);
+        }
+        let sample_rate = self.sample_rate;
+        let volume = self.monitor_volume.clone();
+        let paused = self.monitor_paused.clone();
+        let events = self.events.clone();
+        thread::spawn(move || {
+            let opened = rodio::OutputStream::try_default()
+                .map_err(anyhow::Error::from)
+                .and_then(|(stream, handle)| Ok((stream, rodio::Sink::try_new(&handle)?)));
+            let (_stream, sink) = match opened {
+                Ok(opened) => opened,
+                Err(e) => {
+                    let _ = events.send(AppEvent::Error(format!("mic monitor unavailable: {e}")));
+                    return;
+                }
+            };
+            // Ends when `stop_monitor` drops the sender.
+            while let Ok(block) = rx.recv() {
+                if paused.load(Ordering::Relaxed) || sink.len() > MONITOR_MAX_QUEUED {
+                    continue;
+                }
+                sink.set_volume(f32::from_bits(volume.load(Ordering::Relaxed)));
+                sink.append(rodio::buffer::SamplesBuffer::new(1, sample_rate, block));
+            }
+        });
+    }
+
+    pub fn stop_monitor(&self) {
+        if let Ok(mut monitor) = self.capture.monitor.lock() {
+            *monitor = None;
+        }
+    }
+
+    pub fn set_monitor_volume(&self, volume: f32) {
+        let volume = volume.clamp(0.0, MAX_MONITOR_VOLUME);
+        self.monitor_volume
+            .store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn pause_monitor(&self, paused: bool) {
+        self.monitor_paused.store(paused, Ordering::Relaxed);
+    }
+
+    // 0..1 peak of the most recent input block, whether or not a capture is running.
+    pub fn input_level(&self) -> f32 {
+        self.capture.peak.load(Ordering::Relaxed) as f32 / i16::MAX as f32
     }
 
     pub fn sample_rate(&self) -> u32 {
@@ -126,14 +394,207 @@ impl AudioRecorder {
     }
 }
 
-fn downmix_i16_to_mono(data: &[i16], channels: usize) -> Vec<i16> {
-    if channels <= 1 {
-        return data.to_vec();
-    }
-    let mut out = Vec::with_capacity(data.len() / channels);
-    for frame in data.chunks_exact(channels) {
-        let sum: i32 = frame.iter().map(|s| *s as i32).sum();
-        out.push((sum / channels as i32) as i16);
-    }
-    out
+pub fn default_input_name() -> Option<String> {
+    cpal::default_host().default_input_device().map(|device| {
+        device
+            .name()
+            .unwrap_or_else(|_| "unnamed device".to_string())
+    })
+}
+
+pub fn default_output_name() -> Option<String> {
+    cpal::default_host().default_output_device().map(|device| {
+        device
+            .name()
+            .unwrap_or_else(|_| "unnamed device".to_string())
+    })
+}
+
+// Only a guess from the device name; plenty of USB and Bluetooth sets don't say.
+pub fn looks_like_headphones(output_name: &str) -> bool {
+    let name = output_name.to_lowercase();
+    [
+        "headphone",
+        "headset",
+        "earphone",
+        "earbud",
+        "buds",
+        "airpods",
+    ]
+    .iter()
+    .any(|hint| name.contains(hint))
+}
+
+// Builds and starts a stream on the default input device. When `target_rate` is set
+// (reconnects), audio is resampled to it so downstream consumers keep the original rate.
+fn open_input_stream(
+    target_rate: Option<u32>,
+    capture: &Arc<CaptureBuffer>,
+    degraded: &Arc<AtomicBool>,
+    events: &Sender<AppEvent>,
+) -> anyhow::Result<(cpal::Stream, u32)> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .context("no default input device available")?;
+    let supported = device.default_input_config()?;
+    let device_rate = supported.sample_rate().0;
+    let sample_rate = target_rate.unwrap_or(device_rate);
+    let channels = supported.channels() as usize;
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+
+    let push = {
+        let capture = capture.clone();
+        move |data: &[i16]| {
+            let keep = capture.downmix_channel.load(Ordering::Relaxed) as usize;
+            let mono = dsp::downmix_i16_to_mono(data, channels, keep);
+            if device_rate == sample_rate {
+                capture.push(&mono);
+            } else {
+                capture.push(&dsp::resample_linear(&mono, device_rate, sample_rate));
+            }
+        }
+    };
+    let on_error = {
+        let degraded = degraded.clone();
+        let events = events.clone();
+        move |err| {
+            if !degraded.swap(true, Ordering::Relaxed) {
+                let _ = events.send(AppEvent::Error(format!(
+                    "audio error: {err}; will keep trying to reconnect"
+                )));
+            }
+        }
+    };
+
+    let stream = match sample_format {
+        cpal::SampleFormat::I16 => {
+            device.build_input_stream(&config, move |data: &[i16], _| push(data), on_error, None)?
+        }
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _| {
+                let converted: Vec<i16> = data
+                    .iter()
+                    .map(|s| (*s as i32 - 32768).clamp(i16::MIN as i32, i16::MAX as i32) as i16)
+                    .collect();
+                push(&converted);
+            },
+            on_error,
+            None,
+        )?,
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _| {
+                let converted: Vec<i16> = data
+                    .iter()
+                    .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
+                    .collect();
+                push(&converted);
+            },
+            on_error,
+            None,
+        )?,
+        cpal::SampleFormat::I32 => device.build_input_stream(
+            &config,
+            move |data: &[i32], _| {
+                let converted: Vec<i16> = data.iter().map(|s| (*s >> 16) as i16).collect();
+                push(&converted);
+            },
+            on_error,
+            None,
+        )?,
+        cpal::SampleFormat::I24 => device.build_input_stream(
+            &config,
+            move |data: &[cpal::I24], _| {
+                let converted: Vec<i16> = data.iter().map(|s| (s.inner() >> 8) as i16).collect();
+                push(&converted);
+            },
+            on_error,
+            None,
+        )?,
+        other => return Err(anyhow!("unsupported sample format: {other}")),
+    };
+    stream.play()?;
+    let _ = events.send(AppEvent::Info(format!(
+        "audio input: {device_rate} Hz, {channels} ch, {sample_format}"
+    )));
+    Ok((stream, sample_rate))
+}
+
+// Rebuilds the input stream after a device error (USB/Bluetooth mics dropping out).
+fn spawn_reconnect_watchdog(
+    sample_rate: u32,
+    capture: Arc<CaptureBuffer>,
+    stream: Arc<Mutex<Option<cpal::Stream>>>,
+    degraded: Arc<AtomicBool>,
+    events: Sender<AppEvent>,
+) {
+    thread::spawn(move || {
+        loop {
+            thread::sleep(RECONNECT_INTERVAL);
+            if !degraded.load(Ordering::Relaxed) {
+                continue;
+            }
+            if let Ok(mut slot) = stream.lock() {
+                slot.take();
+            }
+            degraded.store(false, Ordering::Relaxed);
+            match open_input_stream(Some(sample_rate), &capture, &degraded, &events) {
+                Ok((next, _)) => {
+                    if let Ok(mut slot) = stream.lock() {
+                        *slot = Some(next);
+                    }
+                    let _ = events.send(AppEvent::Info("audio input reconnected".to_string()));
+                }
+                Err(_) => degraded.store(true, Ordering::Relaxed),
+            }
+        }
+    });
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Cue {
+    Start,
+    Stop,
+}
+
+const CUE_RATE: u32 = 24_000;
+const CUE_MS: u32 = 80;
+const CUE_VOLUME: f32 = 0.2;
+
+// Fire-and-forget beep on its own thread so the capture path never waits on
+// the output device. Start is a rising pitch, stop a lower one.
+pub fn play_cue(cue: Cue) {
+    thread::spawn(move || {
+        let Ok((_stream, handle)) = rodio::OutputStream::try_default() else {
+            return;
+        };
+        let Ok(sink) = rodio::Sink::try_new(&handle) else {
+            return;
+        };
+        let freq = match cue {
+            Cue::Start => 880.0,
+            Cue::Stop => 587.0,
+        };
+        sink.append(rodio::buffer::SamplesBuffer::new(
+            1,
+            CUE_RATE,
+            cue_tone(freq),
+        ));
+        sink.sleep_until_end();
+    });
+}
+
+fn cue_tone(freq: f32) -> Vec<f32> {
+    let len = (CUE_RATE * CUE_MS / 1000) as usize;
+    // 5 ms fades keep the tone from clicking at either end.
+    let fade = (CUE_RATE / 200) as usize;
+    (0..len)
+        .map(|i| {
+            let envelope = (i.min(len - 1 - i) as f32 / fade as f32).min(1.0);
+            (2.0 * PI * freq * i as f32 / CUE_RATE as f32).sin() * CUE_VOLUME * envelope
+        })
+        .collect()
 }
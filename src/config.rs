@@ -1,8 +1,19 @@
-use std::{collections::HashMap, fs, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, OnceLock},
+};
 
 use anyhow::Context;
+use crossbeam_channel::Sender;
 use serde::{Deserialize, Serialize};
 
+use crate::app::AppEvent;
+
+pub const CONFIG_VERSION: u32 = 2;
+const CONFIG_PATH_ENV: &str = "PUSH2TYPE_CONFIG";
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Provider {
     #[serde(rename = "xai")]
@@ -11,30 +22,385 @@ pub enum Provider {
     OpenAi,
     #[serde(rename = "groq")]
     Groq,
+    // TTS only; voices are account-specific IDs.
+    #[serde(rename = "elevenlabs")]
+    ElevenLabs,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HotkeyMode {
+    #[default]
+    #[serde(rename = "hold")]
+    Hold,
+    #[serde(rename = "toggle")]
+    Toggle,
+    #[serde(rename = "double_tap_latch")]
+    DoubleTapLatch,
+}
+
+impl HotkeyMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            HotkeyMode::Hold => "hold",
+            HotkeyMode::Toggle => "toggle",
+            HotkeyMode::DoubleTapLatch => "double-tap latch",
+        }
+    }
+}
+
+// How multi-channel input is folded to mono. `Channel` is 1-based, matching how
+// interfaces label their inputs.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DownmixMode {
+    #[default]
+    #[serde(rename = "average")]
+    Average,
+    #[serde(rename = "left")]
+    Left,
+    #[serde(rename = "right")]
+    Right,
+    #[serde(rename = "channel")]
+    Channel(u16),
+}
+
+impl DownmixMode {
+    pub fn label(self) -> String {
+        match self {
+            DownmixMode::Average => "average".to_string(),
+            DownmixMode::Left => "left".to_string(),
+            DownmixMode::Right => "right".to_string(),
+            DownmixMode::Channel(n) => format!("channel {n}"),
+        }
+    }
+
+    // 0 = average all channels, otherwise the 1-based channel to keep.
+    pub fn channel(self) -> u16 {
+        match self {
+            DownmixMode::Average => 0,
+            DownmixMode::Left => 1,
+            DownmixMode::Right => 2,
+            DownmixMode::Channel(n) => n,
+        }
+    }
+}
+
+// Container for hosted STT uploads. WAV is accepted everywhere; FLAC is
+// lossless and roughly half the size.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UploadFormat {
+    #[default]
+    #[serde(rename = "wav")]
+    Wav,
+    #[serde(rename = "flac")]
+    Flac,
+}
+
+impl UploadFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            UploadFormat::Wav => "WAV",
+            UploadFormat::Flac => "FLAC",
+        }
+    }
+}
+
+// Where dictation is transcribed. Local runs whisper.cpp in-process on
+// `local_whisper_model` and needs a build with `--features local-whisper`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SttBackend {
+    #[default]
+    #[serde(rename = "hosted")]
+    Hosted,
+    #[serde(rename = "local")]
+    Local,
+}
+
+// Where a transcript lands relative to the caret. ReplaceSelection is the plain
+// paste (a paste already replaces any selection); it's listed so the intent is explicit.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum InjectPlacement {
+    #[default]
+    #[serde(rename = "cursor")]
+    Cursor,
+    #[serde(rename = "end_of_line")]
+    EndOfLine,
+    #[serde(rename = "replace_selection")]
+    ReplaceSelection,
+}
+
+impl InjectPlacement {
+    pub fn label(self) -> &'static str {
+        match self {
+            InjectPlacement::Cursor => "at cursor",
+            InjectPlacement::EndOfLine => "end of line",
+            InjectPlacement::ReplaceSelection => "replace selection",
+        }
+    }
+}
+
+// What to do with captures that queued up while a transcription was in flight.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SttCoalesce {
+    #[default]
+    #[serde(rename = "fifo")]
+    Fifo,
+    #[serde(rename = "merge")]
+    Merge,
+    #[serde(rename = "latest")]
+    Latest,
+}
+
+impl SttCoalesce {
+    pub fn label(self) -> &'static str {
+        match self {
+            SttCoalesce::Fifo => "one by one",
+            SttCoalesce::Merge => "merge queued",
+            SttCoalesce::Latest => "newest only",
+        }
+    }
+}
+
+// What the TTS worker does with a message over `tts_max_chars`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TtsOverflowPolicy {
+    #[default]
+    #[serde(rename = "truncate")]
+    Truncate,
+    #[serde(rename = "reject")]
+    Reject,
+}
+
+// What happens to speech already playing when a capture starts, so the mic doesn't
+// transcribe it back.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ListenDuck {
+    #[default]
+    #[serde(rename = "off")]
+    Off,
+    #[serde(rename = "duck")]
+    Duck,
+    #[serde(rename = "pause")]
+    Pause,
+}
+
+impl ListenDuck {
+    pub fn label(self) -> &'static str {
+        match self {
+            ListenDuck::Off => "keep playing",
+            ListenDuck::Duck => "duck",
+            ListenDuck::Pause => "pause",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BindingAction {
+    #[default]
+    #[serde(rename = "dictate")]
+    Dictate,
+    #[serde(rename = "speak")]
+    Speak,
+    #[serde(rename = "speak_last")]
+    SpeakLast,
+    #[serde(rename = "cancel")]
+    Cancel,
+}
+
+impl BindingAction {
+    pub fn label(self) -> &'static str {
+        match self {
+            BindingAction::Dictate => "dictate",
+            BindingAction::Speak => "speak",
+            BindingAction::SpeakLast => "speak last transcript",
+            BindingAction::Cancel => "cancel",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    #[serde(rename = "system")]
+    System,
+    #[serde(rename = "dark")]
+    Dark,
+    #[serde(rename = "light")]
+    Light,
+}
+
+impl Theme {
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::System => "system",
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        }
+    }
+}
+
+// What the in-app log panel shows. Ordered so a line is shown when its level is
+// <= the setting; errors always pass. The log file is never filtered.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "warn")]
+    Warn,
+    #[default]
+    #[serde(rename = "info")]
+    Info,
+}
+
+impl LogLevel {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "errors",
+            LogLevel::Warn => "warnings + errors",
+            LogLevel::Info => "everything",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct AppConfig {
+    pub config_version: u32,
     pub hotkey: String,
+    pub cancel_hotkey: String,
+    pub hotkey_mode: HotkeyMode,
+    pub hotkey_bindings: Vec<HotkeyBinding>,
+    pub double_tap_ms: u64,
+    pub min_capture_ms: u64,
+    pub capture_cooldown_ms: u64,
+    pub release_grace_ms: u64,
+    pub cue_sounds: bool,
+    pub input_gain: f32,
+    pub monitor_volume: f32,
+    pub downmix_mode: DownmixMode,
+    pub preroll_ms: u64,
+    // Capture buffer space reserved up front, so the audio callback doesn't reallocate
+    // during captures up to this long.
+    pub capture_reserve_secs: u32,
+    pub noise_gate_enabled: bool,
+    pub noise_gate_threshold: u16,
+    pub vad_enabled: bool,
+    pub vad_silence_ms: u64,
+    pub vad_threshold: u16,
     pub stt_model: String,
     pub stt_language: String,
+    pub stt_language_by_provider: HashMap<String, String>,
+    pub stt_prompt: String,
+    pub stt_min_confidence: f32,
+    pub stt_streaming: bool,
+    pub stt_upload_format: UploadFormat,
+    pub stt_coalesce: SttCoalesce,
     pub stt_provider: Provider,
+    pub stt_backend: SttBackend,
+    pub local_whisper_model: String,
+    pub save_recordings_dir: Option<String>,
     pub tts_provider: Provider,
     pub xai_voice: String,
     pub openai_voice: String,
     pub groq_voice: String,
+    pub elevenlabs_voice: String,
     pub xai_realtime_model: String,
+    pub xai_reconnect_attempts: u32,
     pub openai_tts_model: String,
     pub groq_tts_model: String,
+    pub elevenlabs_tts_model: String,
     pub groq_stt_model: String,
     pub stt_models: HashMap<String, Vec<String>>,
     pub stt_model_by_provider: HashMap<String, String>,
+    pub stt_base_urls: HashMap<String, String>,
+    pub tts_base_urls: HashMap<String, String>,
     pub xai_tts_style: String,
+    pub tts_verbatim: bool,
+    pub tts_chunk_chars: usize,
+    pub tts_max_chars: usize,
+    pub tts_overflow_policy: TtsOverflowPolicy,
+    pub tts_normalize: bool,
+    pub tts_target_peak: f32,
+    pub tts_keep_output_open: bool,
+    pub duck_tts_on_listen: ListenDuck,
+    pub http_timeout_secs: u64,
     pub server_port: u16,
     pub tts_bridge_enabled: bool,
+    pub server_auth_token: String,
+    // `/speak` requests allowed per minute across all clients; 0 = unlimited.
+    pub speak_rate_per_min: u32,
     pub show_endpoint_text: bool,
+    pub log_to_file: bool,
+    pub log_level: LogLevel,
+    pub log_max_lines: usize,
+    pub theme: Theme,
+    // sRGB color of the window border and dot shown while recording.
+    pub listen_accent: [u8; 3],
+    pub always_on_top: bool,
+    // Content-driven window height; off keeps the saved size as is.
+    pub auto_resize: bool,
+    // Last window geometry in logical points, restored at startup.
+    pub window_x: Option<f32>,
+    pub window_y: Option<f32>,
+    pub window_w: Option<f32>,
+    pub window_h: Option<f32>,
     pub persona_voices: HashMap<String, String>,
+    pub persona_styles: HashMap<String, String>,
+    pub voice_aliases: HashMap<String, VoiceAlias>,
+    pub replacements: Vec<ReplacementRule>,
+    pub strip_prefixes: Vec<String>,
+    pub inject_suffix: String,
+    // Shell command the transcript is piped through (stdin -> stdout) before pasting.
+    pub inject_command: Option<String>,
+    pub inject_command_timeout_ms: u64,
+    pub inject_settle_ms: u64,
+    // Windows only: the foreground window must stay the same this long before pasting,
+    // waiting at most `inject_focus_timeout_ms`. 0 skips the check.
+    pub inject_focus_stable_ms: u64,
+    pub inject_focus_timeout_ms: u64,
+    pub inject_verify: bool,
+    pub inject_submit: bool,
+    pub inject_placement: InjectPlacement,
+    pub auto_capitalize: bool,
+    pub auto_period: bool,
+    pub speak_transcript_back: bool,
+    // Canned STT text and a beep for TTS; no network calls or API keys needed.
+    pub mock_mode: bool,
+    pub inject_enabled: bool,
+    pub paste_shortcut: String,
+    pub inject_allowlist: Vec<String>,
+    // Keys this build doesn't know about; kept so saving doesn't drop them.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+// Extra hotkeys on top of `hotkey`/`cancel_hotkey`. Dictate bindings may pick their
+// own STT provider/model (None/empty = main settings); speak bindings say `message`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct HotkeyBinding {
+    pub hotkey: String,
+    pub action: BindingAction,
+    pub stt_provider: Option<Provider>,
+    pub stt_model: String,
+    pub stt_language: String,
+    pub message: String,
+    pub persona: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ReplacementRule {
+    pub find: String,
+    pub replace: String,
+    // `find` is a regex and `replace` may use `$1`-style groups; off matches literally.
+    pub regex: bool,
+}
+
+// A friendly name that stands for a provider + voice pair wherever a voice is accepted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VoiceAlias {
+    pub provider: Provider,
+    pub voice: String,
 }
 
 impl Default for AppConfig {
@@ -45,45 +411,150 @@ impl Default for AppConfig {
         persona_voices.insert("planner".to_string(), "eve".to_string());
 
         Self {
+            config_version: CONFIG_VERSION,
             hotkey: "ctrl+shift".to_string(),
+            cancel_hotkey: String::new(),
+            hotkey_mode: HotkeyMode::Hold,
+            hotkey_bindings: Vec::new(),
+            double_tap_ms: 300,
+            min_capture_ms: 200,
+            capture_cooldown_ms: 0,
+            release_grace_ms: 0,
+            cue_sounds: false,
+            input_gain: 1.0,
+            monitor_volume: 0.3,
+            downmix_mode: DownmixMode::Average,
+            preroll_ms: 300,
+            capture_reserve_secs: 60,
+            noise_gate_enabled: false,
+            noise_gate_threshold: 500,
+            vad_enabled: false,
+            vad_silence_ms: 700,
+            vad_threshold: 600,
             stt_model: "gpt-4o-mini-transcribe-2025-12-15".to_string(),
             stt_language: "en".to_string(),
+            stt_language_by_provider: HashMap::new(),
+            stt_prompt: String::new(),
+            stt_min_confidence: 0.0,
+            stt_streaming: false,
+            stt_upload_format: UploadFormat::Wav,
+            stt_coalesce: SttCoalesce::Fifo,
             stt_provider: Provider::OpenAi,
+            stt_backend: SttBackend::Hosted,
+            local_whisper_model: String::new(),
+            save_recordings_dir: None,
             tts_provider: Provider::Xai,
             xai_voice: "rex".to_string(),
             openai_voice: "alloy".to_string(),
             groq_voice: "troy".to_string(),
+            elevenlabs_voice: "21m00Tcm4TlvDq8ikWAM".to_string(),
             xai_realtime_model: "grok-4-voice".to_string(),
+            xai_reconnect_attempts: 2,
             openai_tts_model: "gpt-4o-mini-tts-2025-12-15".to_string(),
             groq_tts_model: "canopylabs/orpheus-v1-english".to_string(),
+            elevenlabs_tts_model: "eleven_flash_v2_5".to_string(),
             groq_stt_model: "whisper-large-v3-turbo".to_string(),
             stt_models: default_stt_models(),
             stt_model_by_provider: default_stt_model_by_provider(),
+            stt_base_urls: HashMap::new(),
+            tts_base_urls: HashMap::new(),
             xai_tts_style: "clear, concise, and technically precise".to_string(),
+            tts_verbatim: true,
+            tts_chunk_chars: 200,
+            tts_max_chars: 0,
+            tts_overflow_policy: TtsOverflowPolicy::Truncate,
+            tts_normalize: false,
+            tts_target_peak: -3.0,
+            tts_keep_output_open: false,
+            duck_tts_on_listen: ListenDuck::Off,
+            http_timeout_secs: 30,
             server_port: 7821,
             tts_bridge_enabled: true,
+            server_auth_token: String::new(),
+            speak_rate_per_min: 0,
             show_endpoint_text: true,
+            log_to_file: false,
+            log_level: LogLevel::Info,
+            log_max_lines: 300,
+            theme: Theme::System,
+            listen_accent: [229, 57, 53],
+            always_on_top: true,
+            auto_resize: true,
+            window_x: None,
+            window_y: None,
+            window_w: None,
+            window_h: None,
             persona_voices,
+            persona_styles: HashMap::new(),
+            voice_aliases: HashMap::new(),
+            replacements: Vec::new(),
+            strip_prefixes: Vec::new(),
+            inject_suffix: String::new(),
+            inject_command: None,
+            inject_command_timeout_ms: 10_000,
+            inject_settle_ms: 85,
+            inject_focus_stable_ms: 60,
+            inject_focus_timeout_ms: 500,
+            inject_verify: true,
+            inject_submit: false,
+            inject_placement: InjectPlacement::Cursor,
+            auto_capitalize: false,
+            auto_period: false,
+            speak_transcript_back: false,
+            mock_mode: false,
+            inject_enabled: true,
+            paste_shortcut: crate::inject::default_paste_shortcut().to_string(),
+            inject_allowlist: Vec::new(),
+            extra: serde_json::Map::new(),
         }
     }
 }
 
 impl AppConfig {
-    pub fn load_or_create() -> anyhow::Result<Self> {
+    // Returns the config plus a note for each schema migration that ran.
+    pub fn load_or_create() -> anyhow::Result<(Self, Vec<String>)> {
         let path = Self::config_path()?;
         if path.exists() {
-            let content = fs::read_to_string(&path)
-                .with_context(|| format!("failed reading config {}", path.display()))?;
-            let cfg: Self = serde_json::from_str(&content)
-                .with_context(|| format!("failed parsing config {}", path.display()))?;
-            Ok(cfg)
+            let (cfg, migrations) = Self::import_from(&path)?;
+            if !migrations.is_empty() {
+                cfg.save()?;
+            }
+            Ok((cfg, migrations))
         } else {
             let cfg = Self::default();
             cfg.save()?;
-            Ok(cfg)
+            Ok((cfg, Vec::new()))
         }
     }
 
+    pub fn export_to(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.export_json()?)
+            .with_context(|| format!("failed writing {}", path.display()))?;
+        Ok(())
+    }
+
+    // API keys already live in env vars; the bridge token is the one secret in the
+    // file itself, so it is blanked before the settings leave the machine.
+    fn export_json(&self) -> anyhow::Result<String> {
+        let mut exported = self.clone();
+        exported.server_auth_token.clear();
+        Ok(serde_json::to_string_pretty(&exported)?)
+    }
+
+    pub fn import_from(path: &Path) -> anyhow::Result<(Self, Vec<String>)> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed reading config {}", path.display()))?;
+        let mut value: serde_json::Value = serde_json::from_str(&content)
+            .with_context(|| format!("failed parsing config {}", path.display()))?;
+        let migrations = migrate(&mut value);
+        let cfg: Self = serde_json::from_value(value)
+            .with_context(|| format!("failed parsing config {}", path.display()))?;
+        Ok((cfg, migrations))
+    }
+
     pub fn save(&self) -> anyhow::Result<()> {
         let path = Self::config_path()?;
         let json = serde_json::to_string_pretty(self)?;
@@ -91,7 +562,16 @@ impl AppConfig {
         Ok(())
     }
 
+    // PUSH2TYPE_CONFIG names the exact file, for portable installs or side-by-side profiles.
     pub fn config_path() -> anyhow::Result<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_PATH_ENV).filter(|path| !path.is_empty()) {
+            let path = PathBuf::from(path);
+            if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+                fs::create_dir_all(dir)
+                    .with_context(|| format!("cannot create {}", dir.display()))?;
+            }
+            return Ok(path);
+        }
         let base = dirs::data_local_dir().context("cannot resolve local data dir")?;
         let dir = base.join("Push2TypeRs");
         fs::create_dir_all(&dir)?;
@@ -99,19 +579,65 @@ impl AppConfig {
     }
 
     pub fn stt_key(&self, provider: &Provider) -> Option<String> {
-        match provider {
-            Provider::Xai => std::env::var("XAI_API_KEY").ok(),
-            Provider::OpenAi => std::env::var("OPENAI_API_KEY").ok(),
-            Provider::Groq => std::env::var("GROQ_API_KEY").ok(),
-        }
+        std::env::var(api_key_env(*provider))
+            .ok()
+            .filter(|key| !key.trim().is_empty())
     }
 
-    pub fn stt_base_url(provider: &Provider) -> &'static str {
-        match provider {
-            Provider::Xai => "https://api.x.ai/v1",
-            Provider::OpenAi => "https://api.openai.com/v1",
-            Provider::Groq => "https://api.groq.com/openai/v1",
+    // Settings safe to hand to local tooling; secrets live in env vars and are never included.
+    pub fn public_view(&self) -> serde_json::Value {
+        serde_json::json!({
+            "stt_provider": self.stt_provider,
+            "stt_model": self.stt_model_for(&self.stt_provider),
+            "stt_language": self.stt_language,
+            "stt_backend": self.stt_backend,
+            "tts_provider": self.tts_provider,
+            "xai_voice": self.xai_voice,
+            "openai_voice": self.openai_voice,
+            "groq_voice": self.groq_voice,
+            "elevenlabs_voice": self.elevenlabs_voice,
+            "xai_realtime_model": self.xai_realtime_model,
+            "openai_tts_model": self.openai_tts_model,
+            "groq_tts_model": self.groq_tts_model,
+            "elevenlabs_tts_model": self.elevenlabs_tts_model,
+            "xai_tts_style": self.xai_tts_style,
+            "persona_voices": self.persona_voices,
+            "persona_styles": self.persona_styles,
+            "server_port": self.server_port,
+            "tts_bridge_enabled": self.tts_bridge_enabled,
+        })
+    }
+
+    pub fn check_keys(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+        if self.mock_mode {
+            return missing;
+        }
+        for (pipeline, provider) in [("STT", self.stt_provider), ("TTS", self.tts_provider)] {
+            if pipeline == "STT" && self.stt_backend == SttBackend::Local {
+                continue;
+            }
+            if !has_api_key(provider) {
+                missing.push(format!(
+                    "{} is not set; {pipeline} provider {} will fail",
+                    api_key_env(provider),
+                    provider_key(provider)
+                ));
+            }
         }
+        missing
+    }
+
+    // `stt_base_urls` / `tts_base_urls` (keyed like `stt_model_by_provider`) point a
+    // provider at a proxy or an OpenAI-compatible server; blank means the official API.
+    pub fn stt_base_url(&self, provider: &Provider) -> String {
+        base_url_override(&self.stt_base_urls, *provider)
+            .unwrap_or_else(|| default_base_url(*provider).to_string())
+    }
+
+    pub fn tts_base_url(&self, provider: &Provider) -> String {
+        base_url_override(&self.tts_base_urls, *provider)
+            .unwrap_or_else(|| default_base_url(*provider).to_string())
     }
 
     pub fn stt_model_for(&self, provider: &Provider) -> String {
@@ -121,7 +647,7 @@ impl AppConfig {
         }
         match provider {
             Provider::Groq => self.groq_stt_model.clone(),
-            Provider::Xai | Provider::OpenAi => self.stt_model.clone(),
+            Provider::Xai | Provider::OpenAi | Provider::ElevenLabs => self.stt_model.clone(),
         }
     }
 
@@ -138,16 +664,129 @@ impl AppConfig {
         self.stt_model_by_provider.insert(key, model.clone());
         match provider {
             Provider::Groq => self.groq_stt_model = model,
-            Provider::Xai | Provider::OpenAi => self.stt_model = model,
+            Provider::Xai | Provider::OpenAi | Provider::ElevenLabs => self.stt_model = model,
+        }
+    }
+
+    // Falls back to the global `stt_language` for providers without their own entry.
+    pub fn stt_language_for(&self, provider: &Provider) -> String {
+        self.stt_language_by_provider
+            .get(provider_key(*provider))
+            .cloned()
+            .unwrap_or_else(|| self.stt_language.clone())
+    }
+
+    pub fn set_stt_language_for(&mut self, provider: Provider, language: String) {
+        let key = provider_key(provider).to_string();
+        self.stt_language_by_provider.insert(key, language);
+    }
+}
+
+fn migrate(value: &mut serde_json::Value) -> Vec<String> {
+    let mut applied = Vec::new();
+    let Some(obj) = value.as_object_mut() else {
+        return applied;
+    };
+    let version = obj
+        .get("config_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    if version < 1 {
+        // v1: per-provider STT models live in `stt_model_by_provider`; seed it from the legacy
+        // single-model fields so an old config keeps the models it had selected.
+        let mut note = "config v0 -> v1";
+        if !obj.contains_key("stt_model_by_provider") {
+            let mut by_provider = serde_json::Map::new();
+            if let Some(model) = obj.get("stt_model").filter(|v| v.is_string()) {
+                by_provider.insert("openai".to_string(), model.clone());
+            }
+            if let Some(model) = obj.get("groq_stt_model").filter(|v| v.is_string()) {
+                by_provider.insert("groq".to_string(), model.clone());
+            }
+            if !by_provider.is_empty() {
+                obj.insert(
+                    "stt_model_by_provider".to_string(),
+                    serde_json::Value::Object(by_provider),
+                );
+                note = "config v0 -> v1: backfilled stt_model_by_provider";
+            }
         }
+        applied.push(note.to_string());
+    }
+
+    if version < 2 {
+        // v2: the `local_whisper` flag became `stt_backend`; whisper.cpp now runs in-process,
+        // so the CLI binary and timeout settings are gone.
+        let mut note = "config v1 -> v2";
+        if obj.get("local_whisper").and_then(|v| v.as_bool()) == Some(true) {
+            obj.insert("stt_backend".to_string(), "local".into());
+            note = "config v1 -> v2: local_whisper is now stt_backend \"local\"";
+        }
+        applied.push(note.to_string());
+    }
+
+    if !applied.is_empty() {
+        obj.insert("config_version".to_string(), CONFIG_VERSION.into());
+    }
+    applied
+}
+
+static LOCK_WARNINGS: OnceLock<Sender<AppEvent>> = OnceLock::new();
+
+// Where `lock` reports a recovered poisoning; set once at startup, before the workers.
+pub fn report_lock_warnings(events: Sender<AppEvent>) {
+    let _ = LOCK_WARNINGS.set(events);
+}
+
+// A worker that panicked mid-update leaves the mutex poisoned; the settings
+// themselves are still usable, so keep going instead of taking every thread down.
+pub fn lock(config: &Mutex<AppConfig>) -> MutexGuard<'_, AppConfig> {
+    config.lock().unwrap_or_else(|poisoned| {
+        if let Some(events) = LOCK_WARNINGS.get() {
+            let _ = events.send(AppEvent::Warning(
+                "config lock was poisoned by a panicked thread; recovering".to_string(),
+            ));
+        }
+        config.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+pub fn api_key_env(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Xai => "XAI_API_KEY",
+        Provider::OpenAi => "OPENAI_API_KEY",
+        Provider::Groq => "GROQ_API_KEY",
+        Provider::ElevenLabs => "ELEVENLABS_API_KEY",
+    }
+}
+
+pub fn has_api_key(provider: Provider) -> bool {
+    std::env::var(api_key_env(provider)).is_ok_and(|key| !key.trim().is_empty())
+}
+
+pub fn default_base_url(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Xai => "https://api.x.ai/v1",
+        Provider::OpenAi => "https://api.openai.com/v1",
+        Provider::Groq => "https://api.groq.com/openai/v1",
+        Provider::ElevenLabs => "https://api.elevenlabs.io/v1",
     }
 }
 
+fn base_url_override(urls: &HashMap<String, String>, provider: Provider) -> Option<String> {
+    urls.get(provider_key(provider))
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
 fn provider_key(provider: Provider) -> &'static str {
     match provider {
         Provider::Xai => "xai",
         Provider::OpenAi => "openai",
         Provider::Groq => "groq",
+        Provider::ElevenLabs => "elevenlabs",
     }
 }
 
@@ -181,3 +820,55 @@ fn default_stt_model_by_provider() -> HashMap<String, String> {
     m.insert("groq".to_string(), "whisper-large-v3-turbo".to_string());
     m
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_leaves_out_the_bridge_token() {
+        let cfg = AppConfig {
+            server_auth_token: "s3cret-bridge-token".to_string(),
+            ..AppConfig::default()
+        };
+        let json = cfg.export_json().unwrap();
+        assert!(!json.contains("s3cret-bridge-token"));
+        let exported: AppConfig = serde_json::from_str(&json).unwrap();
+        assert!(exported.server_auth_token.is_empty());
+        assert_eq!(cfg.server_auth_token, "s3cret-bridge-token");
+    }
+
+    #[test]
+    fn migrate_only_reports_a_backfill_it_made() {
+        let mut legacy = serde_json::json!({ "stt_model": "whisper-1" });
+        assert_eq!(
+            migrate(&mut legacy),
+            vec![
+                "config v0 -> v1: backfilled stt_model_by_provider",
+                "config v1 -> v2"
+            ]
+        );
+        assert_eq!(legacy["stt_model_by_provider"]["openai"], "whisper-1");
+
+        let mut bare = serde_json::json!({});
+        assert_eq!(
+            migrate(&mut bare),
+            vec!["config v0 -> v1", "config v1 -> v2"]
+        );
+        assert!(bare.get("stt_model_by_provider").is_none());
+        assert_eq!(bare["config_version"], CONFIG_VERSION);
+
+        assert!(migrate(&mut bare).is_empty());
+    }
+
+    #[test]
+    fn migrate_moves_local_whisper_to_stt_backend() {
+        let mut v1 = serde_json::json!({ "config_version": 1, "local_whisper": true });
+        assert_eq!(
+            migrate(&mut v1),
+            vec!["config v1 -> v2: local_whisper is now stt_backend \"local\""]
+        );
+        let cfg: AppConfig = serde_json::from_value(v1).unwrap();
+        assert_eq!(cfg.stt_backend, SttBackend::Local);
+    }
+}
@@ -0,0 +1,141 @@
+// Tools -> "Run Diagnostics": walks the chain one link at a time through the same
+// recorder, STT worker and TTS worker the hotkeys use. Each step is sent back as it
+// finishes so a broken link is obvious; the channel closes when the run is over.
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use crossbeam_channel::{Receiver, Sender, bounded, unbounded};
+
+use crate::{
+    audio::{self, AudioRecorder},
+    config::{self, AppConfig},
+    dsp,
+    stt::SttJob,
+    tts::{SpeakRequest, TtsProgress, TtsRequest},
+};
+
+const MIC_CLIP: Duration = Duration::from_secs(1);
+// Below this the input is muted, unplugged or the wrong device.
+const MIC_SILENT_DBFS: f32 = -60.0;
+const TEST_TONE_HZ: f32 = 440.0;
+const TEST_TONE_MS: u32 = 1000;
+const TTS_MESSAGE: &str = "Diagnostics complete. Speech output is working.";
+// On top of the HTTP timeout, for queueing and playback.
+const WAIT_MARGIN: Duration = Duration::from_secs(20);
+
+pub struct Step {
+    pub name: &'static str,
+    pub outcome: Result<String, String>,
+}
+
+pub fn spawn(
+    config: Arc<Mutex<AppConfig>>,
+    recorder: Arc<AudioRecorder>,
+    stt_tx: Sender<SttJob>,
+    tts_tx: Sender<TtsRequest>,
+) -> Receiver<Step> {
+    let (steps_tx, steps_rx) = unbounded();
+    thread::spawn(move || {
+        let step = |name: &'static str, outcome: Result<String, String>| {
+            let _ = steps_tx.send(Step { name, outcome });
+        };
+        let wait = Duration::from_secs(config::lock(&config).http_timeout_secs) + WAIT_MARGIN;
+        step("input device", input_device());
+        step("microphone level", mic_level(&recorder));
+        step("API keys", api_keys(&config));
+        step("STT round trip", stt_round_trip(&recorder, &stt_tx, wait));
+        step("TTS playback", tts_playback(&tts_tx, wait));
+    });
+    steps_rx
+}
+
+fn input_device() -> Result<String, String> {
+    audio::default_input_name().ok_or_else(|| "no default input device found".to_string())
+}
+
+fn mic_level(recorder: &AudioRecorder) -> Result<String, String> {
+    recorder.start_test_capture();
+    thread::sleep(MIC_CLIP);
+    let samples = recorder.stop_capture();
+    if samples.is_empty() {
+        return Err("no audio captured; is the input device connected?".to_string());
+    }
+    let level = dsp::rms_dbfs(&samples);
+    if level < MIC_SILENT_DBFS {
+        Err(format!(
+            "RMS {level:.1} dBFS, input looks silent; check mute and the selected device"
+        ))
+    } else {
+        Ok(format!("RMS {level:.1} dBFS over {}s", MIC_CLIP.as_secs()))
+    }
+}
+
+fn api_keys(config: &Arc<Mutex<AppConfig>>) -> Result<String, String> {
+    let missing = config::lock(config).check_keys();
+    if missing.is_empty() {
+        Ok("keys set for the STT and TTS providers".to_string())
+    } else {
+        Err(missing.join("; "))
+    }
+}
+
+// A tone rather than speech: this checks that the provider answers, not accuracy.
+fn stt_round_trip(
+    recorder: &AudioRecorder,
+    stt_tx: &Sender<SttJob>,
+    wait: Duration,
+) -> Result<String, String> {
+    let clip = dsp::sine_tone(TEST_TONE_HZ, recorder.sample_rate(), TEST_TONE_MS, 0.1);
+    let (reply_tx, reply_rx) = bounded(1);
+    stt_tx
+        .send(SttJob::diagnostic(clip, reply_tx))
+        .map_err(|_| "STT worker is not running".to_string())?;
+    match reply_rx.recv_timeout(wait) {
+        Ok((provider, Ok(text))) if text.is_empty() => Ok(format!("{provider} answered")),
+        Ok((provider, Ok(text))) => Ok(format!("{provider} answered: \"{text}\"")),
+        Ok((provider, Err(e))) => Err(format!("{provider}: {e}")),
+        Err(_) => Err(format!(
+            "no answer from the STT worker after {}s",
+            wait.as_secs()
+        )),
+    }
+}
+
+fn tts_playback(tts_tx: &Sender<TtsRequest>, wait: Duration) -> Result<String, String> {
+    let (progress_tx, progress_rx) = unbounded();
+    let request = TtsRequest {
+        speak: SpeakRequest {
+            message: TTS_MESSAGE.to_string(),
+            persona: None,
+            voice: None,
+            provider: None,
+            show_text: Some(false),
+            style: None,
+        },
+        progress: Some(progress_tx),
+    };
+    tts_tx
+        .send(request)
+        .map_err(|_| "TTS worker is not running".to_string())?;
+    let mut chunk_error = None;
+    loop {
+        match progress_rx.recv_timeout(wait) {
+            Ok(TtsProgress::Done { played, total }) if played == total => {
+                return Ok("confirmation played".to_string());
+            }
+            Ok(TtsProgress::Done { played, total }) => {
+                let error = chunk_error.unwrap_or_default();
+                return Err(format!("{played}/{total} chunks played; {error}"));
+            }
+            Ok(TtsProgress::ChunkFailed { error, .. }) => chunk_error = Some(error),
+            Ok(TtsProgress::Rejected(e) | TtsProgress::Failed(e)) => return Err(e),
+            Ok(_) => {}
+            Err(_) => {
+                return Err(format!("TTS didn't finish within {}s", wait.as_secs()));
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+// Pure sample-buffer helpers shared by capture, STT upload and TTS decode. No I/O
+// beyond in-memory buffers, so everything here is covered by the tests below.
+use std::{f32::consts::PI, io::Cursor};
+
+pub fn resample_linear(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    if from == to || samples.is_empty() || from == 0 {
+        return samples.to_vec();
+    }
+    let out_len = (samples.len() as u64 * to as u64 / from as u64) as usize;
+    let step = from as f64 / to as f64;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let idx = pos as usize;
+            let frac = pos - idx as f64;
+            let a = samples[idx.min(samples.len() - 1)] as f64;
+            let b = samples[(idx + 1).min(samples.len() - 1)] as f64;
+            (a + (b - a) * frac).round() as i16
+        })
+        .collect()
+}
+
+const HIGH_PASS_HZ: f32 = 80.0;
+const GATE_ATTACK_SECS: f32 = 0.005;
+const GATE_RELEASE_SECS: f32 = 0.08;
+const ENVELOPE_RELEASE_SECS: f32 = 0.05;
+
+// One-pole high-pass to drop hum, then an amplitude gate. The gate gain ramps
+// instead of switching so opening/closing doesn't click.
+pub fn apply_noise_gate(samples: &mut [i16], sample_rate: u32, threshold: u16) {
+    if samples.is_empty() || sample_rate == 0 {
+        return;
+    }
+    let rate = sample_rate as f32;
+    let rc = 1.0 / (2.0 * PI * HIGH_PASS_HZ);
+    let alpha = rc / (rc + 1.0 / rate);
+    let smoothing = |secs: f32| 1.0 - (-1.0 / (secs * rate)).exp();
+    let attack = smoothing(GATE_ATTACK_SECS);
+    let release = smoothing(GATE_RELEASE_SECS);
+    let envelope_release = smoothing(ENVELOPE_RELEASE_SECS);
+    let threshold = threshold as f32;
+
+    let mut prev_in = samples[0] as f32;
+    let mut prev_out = 0.0f32;
+    let mut envelope = 0.0f32;
+    let mut gain = 0.0f32;
+    for sample in samples.iter_mut() {
+        let x = *sample as f32;
+        let y = alpha * (prev_out + x - prev_in);
+        prev_in = x;
+        prev_out = y;
+
+        let level = y.abs();
+        if level > envelope {
+            envelope = level;
+        } else {
+            envelope += (level - envelope) * envelope_release;
+        }
+        let target = if envelope >= threshold { 1.0 } else { 0.0 };
+        let coeff = if target > gain { attack } else { release };
+        gain += (target - gain) * coeff;
+
+        *sample = (y * gain).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+    }
+}
+
+// `keep` is a 1-based channel to pass through; 0, or a channel the device
+// doesn't have, averages all of them.
+pub fn downmix_i16_to_mono(data: &[i16], channels: usize, keep: usize) -> Vec<i16> {
+    if channels <= 1 {
+        return data.to_vec();
+    }
+    if (1..=channels).contains(&keep) {
+        return data
+            .chunks_exact(channels)
+            .map(|frame| frame[keep - 1])
+            .collect();
+    }
+    let mut out = Vec::with_capacity(data.len() / channels);
+    for frame in data.chunks_exact(channels) {
+        let sum: i32 = frame.iter().map(|s| *s as i32).sum();
+        out.push((sum / channels as i32) as i16);
+    }
+    out
+}
+
+// RMS relative to full scale, floored at one LSB (about -90 dBFS) so silence stays finite.
+pub fn rms_dbfs(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 20.0 * (1.0 / i16::MAX as f32).log10();
+    }
+    let mean_square =
+        samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64;
+    let rms = (mean_square.sqrt() as f32).max(1.0);
+    20.0 * (rms / i16::MAX as f32).log10()
+}
+
+// Quiet sine at `freq`, e.g. as a stand-in capture when no real audio is wanted.
+pub fn sine_tone(freq: f32, sample_rate: u32, duration_ms: u32, amplitude: f32) -> Vec<i16> {
+    let len = (sample_rate as u64 * duration_ms as u64 / 1000) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            ((2.0 * PI * freq * t).sin() * amplitude * i16::MAX as f32) as i16
+        })
+        .collect()
+}
+
+// Splits into `pieces` roughly equal parts, moving each cut to the quietest 20 ms within a
+// second of the even split point so a word isn't halved between two uploads.
+pub fn split_at_quiet(samples: &[i16], pieces: usize, sample_rate: u32) -> Vec<&[i16]> {
+    let window = (sample_rate as usize / 50).max(1);
+    let reach = sample_rate as usize;
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for k in 1..pieces {
+        let target = samples.len() * k / pieces;
+        let lo = target.saturating_sub(reach).max(start + window);
+        let hi = (target + reach).min(samples.len().saturating_sub(window));
+        let cut = (lo..=hi)
+            .step_by(window)
+            .min_by_key(|&at| {
+                samples[at..at + window]
+                    .iter()
+                    .map(|s| s.unsigned_abs() as u64)
+                    .sum::<u64>()
+            })
+            .map_or(target, |at| at + window / 2);
+        if cut > start && cut < samples.len() {
+            parts.push(&samples[start..cut]);
+            start = cut;
+        }
+    }
+    parts.push(&samples[start..]);
+    parts
+}
+
+pub fn pcm_to_wav_bytes(samples: &[i16], sample_rate: u32) -> anyhow::Result<Vec<u8>> {
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = hound::WavWriter::new(
+        &mut cursor,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+    )?;
+    for &sample in samples {
+        writer.write_sample(sample)?;
+    }
+    writer.finalize()?;
+    Ok(cursor.into_inner())
+}
+
+// Downmixes to mono; returns the samples plus the file's sample rate.
+pub fn decode_wav_to_i16(bytes: &[u8]) -> anyhow::Result<(Vec<i16>, u32)> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(anyhow::anyhow!("invalid wav header"));
+    }
+
+    let mut offset = 12usize;
+    let mut audio_format: Option<u16> = None;
+    let mut channels: Option<u16> = None;
+    let mut sample_rate: Option<u32> = None;
+    let mut bits_per_sample: Option<u16> = None;
+    let mut data_slice: Option<&[u8]> = None;
+
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = u32::from_le_bytes([
+            bytes[offset + 4],
+            bytes[offset + 5],
+            bytes[offset + 6],
+            bytes[offset + 7],
+        ]) as usize;
+        let chunk_start = offset + 8;
+        if chunk_start > bytes.len() {
+            break;
+        }
+        let chunk_end = (chunk_start + chunk_size).min(bytes.len());
+
+        if chunk_id == b"fmt " && chunk_end >= chunk_start + 16 {
+            audio_format = Some(u16::from_le_bytes([
+                bytes[chunk_start],
+                bytes[chunk_start + 1],
+            ]));
+            channels = Some(u16::from_le_bytes([
+                bytes[chunk_start + 2],
+                bytes[chunk_start + 3],
+            ]));
+            sample_rate = Some(u32::from_le_bytes([
+                bytes[chunk_start + 4],
+                bytes[chunk_start + 5],
+                bytes[chunk_start + 6],
+                bytes[chunk_start + 7],
+            ]));
+            bits_per_sample = Some(u16::from_le_bytes([
+                bytes[chunk_start + 14],
+                bytes[chunk_start + 15],
+            ]));
+        } else if chunk_id == b"data" {
+            data_slice = Some(&bytes[chunk_start..chunk_end]);
+            break;
+        }
+
+        offset = chunk_start + chunk_size + (chunk_size % 2);
+    }
+
+    let fmt = audio_format.ok_or_else(|| anyhow::anyhow!("wav fmt chunk missing"))?;
+    let ch = channels.ok_or_else(|| anyhow::anyhow!("wav channels missing"))?;
+    let rate = sample_rate.ok_or_else(|| anyhow::anyhow!("wav sample_rate missing"))?;
+    let bps = bits_per_sample.ok_or_else(|| anyhow::anyhow!("wav bits_per_sample missing"))?;
+    let data = data_slice.ok_or_else(|| anyhow::anyhow!("wav data chunk missing"))?;
+
+    let sample_bytes = (bps / 8) as usize;
+    if sample_bytes == 0 {
+        return Err(anyhow::anyhow!("invalid wav sample size"));
+    }
+    let frame_bytes = sample_bytes * ch as usize;
+    if frame_bytes == 0 {
+        return Err(anyhow::anyhow!("invalid wav frame size"));
+    }
+    let valid_len = (data.len() / frame_bytes) * frame_bytes;
+    let payload = &data[..valid_len];
+
+    let mut out = Vec::with_capacity(payload.len() / frame_bytes);
+    for frame in payload.chunks_exact(frame_bytes) {
+        let mut sum = 0i32;
+        for channel_idx in 0..ch as usize {
+            let start = channel_idx * sample_bytes;
+            let s = decode_wav_sample_to_i16(fmt, bps, &frame[start..start + sample_bytes])?;
+            sum += s as i32;
+        }
+        out.push((sum / ch as i32) as i16);
+    }
+    Ok((out, rate))
+}
+
+fn decode_wav_sample_to_i16(
+    format: u16,
+    bits_per_sample: u16,
+    bytes: &[u8],
+) -> anyhow::Result<i16> {
+    match (format, bits_per_sample) {
+        (1, 8) => {
+            let v = bytes[0] as i32 - 128;
+            Ok((v << 8) as i16)
+        }
+        (1, 16) => Ok(i16::from_le_bytes([bytes[0], bytes[1]])),
+        (1, 24) => {
+            let b0 = bytes[0] as i32;
+            let b1 = (bytes[1] as i32) << 8;
+            let b2 = (bytes[2] as i32) << 16;
+            let mut v = b0 | b1 | b2;
+            if (v & 0x800000) != 0 {
+                v |= !0x00FF_FFFF;
+            }
+            Ok((v >> 8) as i16)
+        }
+        (1, 32) => {
+            let v = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            Ok((v >> 16) as i16)
+        }
+        (3, 32) => {
+            let f = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+            Ok((f.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
+        }
+        (3, 64) => {
+            let f = f64::from_le_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ]);
+            Ok((f.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16)
+        }
+        _ => Err(anyhow::anyhow!(
+            "unsupported wav format code {} / {} bits",
+            format,
+            bits_per_sample
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minimal RIFF/WAVE with one fmt chunk and one data chunk.
+    fn wav(format: u16, channels: u16, rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+        let mut out = Vec::new();
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&format.to_le_bytes());
+        out.extend_from_slice(&channels.to_le_bytes());
+        out.extend_from_slice(&rate.to_le_bytes());
+        out.extend_from_slice(&(rate * block_align as u32).to_le_bytes());
+        out.extend_from_slice(&block_align.to_le_bytes());
+        out.extend_from_slice(&bits.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    fn pcm_wav_round_trip() {
+        let samples: Vec<i16> = vec![0, 1, -1, 1234, -1234, i16::MAX, i16::MIN];
+        let bytes = pcm_to_wav_bytes(&samples, 16_000).unwrap();
+        let (decoded, rate) = decode_wav_to_i16(&bytes).unwrap();
+        assert_eq!(rate, 16_000);
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn round_trip_of_empty_buffer() {
+        let bytes = pcm_to_wav_bytes(&[], 24_000).unwrap();
+        assert_eq!(decode_wav_to_i16(&bytes).unwrap(), (Vec::new(), 24_000));
+    }
+
+    #[test]
+    fn decodes_8_bit_unsigned() {
+        let (samples, _) = decode_wav_to_i16(&wav(1, 1, 8000, 8, &[128, 255, 0])).unwrap();
+        assert_eq!(samples, vec![0, 127 << 8, -128 << 8]);
+    }
+
+    #[test]
+    fn decodes_24_bit_with_sign_extension() {
+        // 0x123456 and -2 (0xFFFFFE): keep the top 16 bits.
+        let data = [0x56, 0x34, 0x12, 0xFE, 0xFF, 0xFF];
+        let (samples, _) = decode_wav_to_i16(&wav(1, 1, 8000, 24, &data)).unwrap();
+        assert_eq!(samples, vec![0x1234, -1]);
+    }
+
+    #[test]
+    fn decodes_32_bit_int() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x4000_0000i32.to_le_bytes());
+        data.extend_from_slice(&i32::MIN.to_le_bytes());
+        let (samples, _) = decode_wav_to_i16(&wav(1, 1, 8000, 32, &data)).unwrap();
+        assert_eq!(samples, vec![0x4000, i16::MIN]);
+    }
+
+    #[test]
+    fn decodes_float_and_clamps() {
+        let mut data = Vec::new();
+        for v in [0.5f32, -1.0, 2.0] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        let (samples, _) = decode_wav_to_i16(&wav(3, 1, 8000, 32, &data)).unwrap();
+        assert_eq!(samples, vec![16_384, -i16::MAX, i16::MAX]);
+
+        let data = (-0.25f64).to_le_bytes();
+        let (samples, _) = decode_wav_to_i16(&wav(3, 1, 8000, 64, &data)).unwrap();
+        assert_eq!(samples, vec![-8192]);
+    }
+
+    #[test]
+    fn decode_averages_channels_and_drops_partial_frames() {
+        let mut data = Vec::new();
+        for v in [100i16, 300, -50, -150] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        // Half a frame of trailing garbage.
+        data.extend_from_slice(&[1, 2]);
+        let (samples, rate) = decode_wav_to_i16(&wav(1, 2, 44_100, 16, &data)).unwrap();
+        assert_eq!(rate, 44_100);
+        assert_eq!(samples, vec![200, -100]);
+    }
+
+    #[test]
+    fn decode_rejects_bad_input() {
+        assert!(decode_wav_to_i16(b"not a wav file").is_err());
+        assert!(decode_wav_to_i16(&wav(2, 1, 8000, 16, &[0, 0])).is_err());
+    }
+
+    #[test]
+    fn downmix_averages_or_picks_a_channel() {
+        let stereo = [100, 0, -200, 0, 50, 50];
+        assert_eq!(downmix_i16_to_mono(&stereo, 2, 0), vec![50, -100, 50]);
+        assert_eq!(downmix_i16_to_mono(&stereo, 2, 1), vec![100, -200, 50]);
+        assert_eq!(downmix_i16_to_mono(&stereo, 2, 2), vec![0, 0, 50]);
+        // A channel the device doesn't have falls back to averaging.
+        assert_eq!(downmix_i16_to_mono(&stereo, 2, 5), vec![50, -100, 50]);
+        let quad = [4, 8, 12, 16, -4, -8, -12, -16];
+        assert_eq!(downmix_i16_to_mono(&quad, 4, 0), vec![10, -10]);
+        assert_eq!(downmix_i16_to_mono(&quad, 4, 3), vec![12, -12]);
+        assert_eq!(downmix_i16_to_mono(&[1, 2, 3], 1, 0), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn resample_scales_length_and_keeps_endpoints() {
+        let ramp: Vec<i16> = (0..480).map(|i| i * 10).collect();
+        let down = resample_linear(&ramp, 48_000, 16_000);
+        assert_eq!(down.len(), 160);
+        assert_eq!(down[0], 0);
+        assert_eq!(down[1], 30);
+        let up = resample_linear(&ramp, 16_000, 48_000);
+        assert_eq!(up.len(), 1440);
+        assert_eq!(resample_linear(&ramp, 16_000, 16_000), ramp);
+    }
+
+    #[test]
+    fn rms_dbfs_of_tone_and_silence() {
+        let tone = sine_tone(1000.0, 16_000, 500, 0.5);
+        assert_eq!(tone.len(), 8000);
+        // A sine's RMS is peak / sqrt(2): 0.5 full scale is about -9 dBFS.
+        assert!((rms_dbfs(&tone) + 9.03).abs() < 0.1, "{}", rms_dbfs(&tone));
+        assert!(rms_dbfs(&[0; 100]) < -90.0);
+        assert_eq!(rms_dbfs(&[]), rms_dbfs(&[0; 100]));
+    }
+
+    #[test]
+    fn split_at_quiet_cuts_in_the_gap_and_keeps_every_sample() {
+        let rate = 1000;
+        let mut samples = sine_tone(200.0, rate, 4000, 0.5);
+        samples[2500..2600].fill(0);
+        let parts = split_at_quiet(&samples, 2, rate);
+        assert_eq!(parts.len(), 2);
+        assert!((2500..2600).contains(&parts[0].len()), "{}", parts[0].len());
+        assert_eq!(parts.concat(), samples);
+
+        assert_eq!(split_at_quiet(&samples, 1, rate), vec![&samples[..]]);
+        let parts = split_at_quiet(&samples, 5, rate);
+        assert_eq!(parts.len(), 5);
+        assert_eq!(parts.concat(), samples);
+    }
+
+    #[test]
+    fn noise_gate_silences_quiet_input_and_passes_speech() {
+        let rate = 16_000;
+        let mut quiet: Vec<i16> = (0..rate)
+            .map(|i| if i % 2 == 0 { 40 } else { -40 })
+            .collect();
+        apply_noise_gate(&mut quiet, rate as u32, 500);
+        assert!(quiet.iter().all(|s| s.abs() <= 1));
+
+        let mut loud: Vec<i16> = (0..rate)
+            .map(|i| ((i as f32 * 2.0 * PI * 440.0 / rate as f32).sin() * 8000.0) as i16)
+            .collect();
+        apply_noise_gate(&mut loud, rate as u32, 500);
+        let peak = loud[rate as usize / 2..]
+            .iter()
+            .map(|s| s.abs())
+            .max()
+            .unwrap();
+        assert!(peak > 6000, "peak {peak}");
+    }
+}
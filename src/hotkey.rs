@@ -1,21 +1,35 @@
+#[cfg(not(target_os = "windows"))]
+use std::collections::HashSet;
 use std::{
-    sync::{Arc, Mutex},
+    cmp::Reverse,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
     thread,
+    time::{Duration, Instant},
 };
-#[cfg(not(target_os = "windows"))]
-use std::collections::HashSet;
 
-use crossbeam_channel::Sender;
-#[cfg(not(target_os = "windows"))]
-use rdev::{EventType, listen};
+#[cfg(target_os = "windows")]
+use crossbeam_channel::Receiver;
+use crossbeam_channel::{Sender, unbounded};
 use rdev::Key;
+#[cfg(not(target_os = "windows"))]
+use rdev::{Button, EventType, listen};
 #[cfg(target_os = "windows")]
 use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
-    GetAsyncKeyState, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_RETURN, VK_RCONTROL,
-    VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SPACE, VK_OEM_3,
+    GetAsyncKeyState, VK_ESCAPE, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MBUTTON, VK_OEM_3,
+    VK_RCONTROL, VK_RETURN, VK_RMENU, VK_RSHIFT, VK_RWIN, VK_SPACE, VK_XBUTTON1, VK_XBUTTON2,
 };
 
-use crate::{app::AppEvent, audio::AudioRecorder, config::AppConfig};
+use crate::{
+    app::{AppEvent, Status},
+    audio::{self, AudioRecorder, Cue, VadSettings},
+    config::{self, AppConfig, BindingAction, HotkeyBinding, HotkeyMode, ListenDuck},
+    stt::{SttJob, SttOverrides},
+    supervisor,
+    tts::{PlaybackInterlock, SpeakRequest, TtsRequest},
+};
 
 #[derive(Debug, Clone)]
 struct HotkeySpec {
@@ -24,8 +38,27 @@ struct HotkeySpec {
     require_alt: bool,
     require_meta: bool,
     key: Option<Key>,
+    button: Option<MouseButton>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MouseButton {
+    Middle,
+    X1,
+    X2,
 }
 
+// Shown next to the hotkey field; each backend needs different OS permissions.
+#[cfg(target_os = "windows")]
+pub const BACKEND: &str = "GetAsyncKeyState polling";
+#[cfg(target_os = "macos")]
+pub const BACKEND: &str = "rdev event tap (needs Accessibility access)";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub const BACKEND: &str = "rdev X11 listener";
+
+// rdev does not report middle/side buttons on macOS.
+const MOUSE_BUTTONS_SUPPORTED: bool = !cfg!(target_os = "macos");
+
 #[derive(Default)]
 #[cfg(not(target_os = "windows"))]
 struct KeyState {
@@ -34,114 +67,748 @@ struct KeyState {
     alt: bool,
     meta: bool,
     pressed_non_mod: HashSet<Key>,
+    pressed_buttons: HashSet<MouseButton>,
+}
+
+#[derive(Clone)]
+pub struct HotkeyControl {
+    reload_tx: Sender<()>,
+    triggers: Triggers,
+}
+
+// Captures started from outside the key listener; the session polls both.
+#[derive(Clone, Default)]
+struct Triggers {
+    ui_held: Arc<AtomicBool>,
+    remote_held: Arc<AtomicBool>,
+}
+
+impl HotkeyControl {
+    pub fn reload(&self) {
+        let _ = self.reload_tx.send(());
+    }
+
+    // The on-screen "Hold to Talk" button; acts as the main hotkey.
+    pub fn set_ui_held(&self, held: bool) {
+        self.triggers.ui_held.store(held, Ordering::Relaxed);
+    }
+
+    // `POST /listen`: records like a main-hotkey press, whatever the hotkey mode, until
+    // this is cleared. Ignored if a capture is already running.
+    pub fn set_remote_held(&self, held: bool) {
+        self.triggers.remote_held.store(held, Ordering::Relaxed);
+    }
+}
+
+struct HotkeySpecs {
+    bindings: Vec<Binding>,
+    mode: HotkeyMode,
+    double_tap: Duration,
+    min_capture: Duration,
+    cooldown: Duration,
+    release_grace: Duration,
+    cue_sounds: bool,
+    duck_tts: ListenDuck,
+    vad: Option<VadSettings>,
+}
+
+struct Binding {
+    spec: HotkeySpec,
+    action: Action,
+}
+
+enum Action {
+    Dictate(SttOverrides),
+    Speak(SpeakRequest),
+    // Same as Speak, with the message filled from the last transcript at press time.
+    SpeakLast(SpeakRequest),
+    Cancel,
 }
 
 pub fn spawn_hotkey_worker(
     config: Arc<Mutex<AppConfig>>,
     events: Sender<AppEvent>,
     recorder: Arc<AudioRecorder>,
-    stt_tx: Sender<Vec<i16>>,
-) {
-    thread::spawn(move || {
-        let hotkey_str = config
-            .lock()
-            .ok()
-            .map(|c| c.hotkey.clone())
-            .unwrap_or_else(|| "ctrl+shift".to_string());
-
-        let spec = parse_hotkey_spec(&hotkey_str).unwrap_or_else(|| {
-            let _ = events.send(AppEvent::Warning(format!(
-                "hotkey '{}' invalid, defaulting to ctrl+shift",
-                hotkey_str
-            )));
-            HotkeySpec {
-                require_ctrl: true,
-                require_shift: true,
-                require_alt: false,
-                require_meta: false,
-                key: None,
-            }
-        });
-        let _ = events.send(AppEvent::Info(format!("hotkey active: {}", hotkey_str)));
+    stt_tx: Sender<SttJob>,
+    tts_tx: Sender<TtsRequest>,
+    status: Arc<Mutex<Status>>,
+    interlock: PlaybackInterlock,
+) -> HotkeyControl {
+    let (reload_tx, reload_rx) = unbounded::<()>();
+    let triggers = Triggers::default();
+    let session_triggers = triggers.clone();
+    let _ = events.send(AppEvent::Info(format!("hotkey backend: {BACKEND}")));
+    supervisor::spawn("hotkey", events.clone(), config.clone(), move || {
+        let specs = load_specs(&config, &events);
+        let mut session = CaptureSession::new(
+            events.clone(),
+            recorder.clone(),
+            stt_tx.clone(),
+            tts_tx.clone(),
+            status.clone(),
+            interlock.clone(),
+            session_triggers.clone(),
+        );
+        session.configure(&specs);
 
         #[cfg(target_os = "windows")]
         {
-            run_windows_hotkey_loop(spec, events, recorder, stt_tx);
+            run_windows_hotkey_loop(
+                specs,
+                config.clone(),
+                reload_rx.clone(),
+                events.clone(),
+                session,
+            );
         }
 
         #[cfg(not(target_os = "windows"))]
         {
-        let state = Arc::new(Mutex::new(KeyState::default()));
-        let active = Arc::new(Mutex::new(false));
-        let cb_events = events.clone();
-        let cb_recorder = recorder.clone();
-        let cb_state = state.clone();
-        let cb_active = active.clone();
-        let cb_stt_tx = stt_tx.clone();
-
-        let result = listen(move |event| {
-            let mut st = match cb_state.lock() {
-                Ok(s) => s,
-                Err(_) => return,
+            let mut specs = specs;
+            let mut state = KeyState::default();
+            let session = Arc::new(Mutex::new(session));
+            let cb_config = config.clone();
+            let cb_reload_rx = reload_rx.clone();
+            let cb_events = events.clone();
+            let cb_session = session.clone();
+
+            // rdev only calls back on input events, so timed transitions need their own tick.
+            // It holds the session weakly so a restarted worker doesn't leave it running.
+            let tick_session = Arc::downgrade(&session);
+            thread::spawn(move || {
+                while let Some(session) = tick_session.upgrade() {
+                    if let Ok(mut session) = session.lock() {
+                        session.tick();
+                    }
+                    drop(session);
+                    thread::sleep(Duration::from_millis(15));
+                }
+            });
+
+            let result = listen(move |event| {
+                let Ok(mut session) = cb_session.lock() else {
+                    return;
+                };
+                if cb_reload_rx.try_iter().count() > 0 {
+                    specs = load_specs(&cb_config, &cb_events);
+                    session.configure(&specs);
+                }
+                update_key_state(&mut state, &event.event_type);
+                let down: Vec<bool> = specs
+                    .bindings
+                    .iter()
+                    .map(|binding| is_hotkey_active(&state, &binding.spec))
+                    .collect();
+                session.update(&specs.bindings, &down);
+            });
+
+            if let Err(e) = result {
+                let message = listen_failure_message(&e);
+                let _ = events.send(AppEvent::Error(message.clone()));
+                let _ = events.send(AppEvent::HotkeyListenerFailed(message));
+                // No global keys, but the on-screen button still drives captures via tick.
+                while reload_rx.recv().is_ok() {
+                    let specs = load_specs(&config, &events);
+                    if let Ok(mut session) = session.lock() {
+                        session.configure(&specs);
+                    }
+                }
+            }
+        }
+    });
+    HotkeyControl {
+        reload_tx,
+        triggers,
+    }
+}
+
+// The main `hotkey` and `cancel_hotkey` come first, then `hotkey_bindings` in order.
+fn load_specs(config: &Arc<Mutex<AppConfig>>, events: &Sender<AppEvent>) -> HotkeySpecs {
+    let cfg = config::lock(config).clone();
+
+    let hotkey = parse_supported_spec(&cfg.hotkey, events).unwrap_or_else(|| {
+        let _ = events.send(AppEvent::Warning(format!(
+            "hotkey '{}' invalid, defaulting to ctrl+shift",
+            cfg.hotkey
+        )));
+        HotkeySpec {
+            require_ctrl: true,
+            require_shift: true,
+            require_alt: false,
+            require_meta: false,
+            key: None,
+            button: None,
+        }
+    });
+    let _ = events.send(AppEvent::Info(format!(
+        "hotkey active: {} (mode: {})",
+        cfg.hotkey,
+        cfg.hotkey_mode.label()
+    )));
+    let mut bindings = vec![Binding {
+        spec: hotkey,
+        action: Action::Dictate(SttOverrides::default()),
+    }];
+    if let Some(cancel) = parse_cancel_spec(&cfg.cancel_hotkey, events) {
+        bindings.push(Binding {
+            spec: cancel,
+            action: Action::Cancel,
+        });
+    }
+    for binding in &cfg.hotkey_bindings {
+        if let Some(parsed) = parse_binding(binding, events) {
+            bindings.push(parsed);
+        }
+    }
+
+    HotkeySpecs {
+        bindings,
+        mode: cfg.hotkey_mode,
+        double_tap: Duration::from_millis(cfg.double_tap_ms),
+        min_capture: Duration::from_millis(cfg.min_capture_ms),
+        cooldown: Duration::from_millis(cfg.capture_cooldown_ms),
+        release_grace: Duration::from_millis(cfg.release_grace_ms),
+        cue_sounds: cfg.cue_sounds,
+        duck_tts: cfg.duck_tts_on_listen,
+        vad: cfg.vad_enabled.then_some(VadSettings {
+            silence_ms: cfg.vad_silence_ms,
+            threshold: cfg.vad_threshold,
+        }),
+    }
+}
+
+fn parse_binding(binding: &HotkeyBinding, events: &Sender<AppEvent>) -> Option<Binding> {
+    let input = binding.hotkey.trim();
+    if input.is_empty() {
+        return None;
+    }
+    let Some(spec) = parse_supported_spec(input, events) else {
+        let _ = events.send(AppEvent::Warning(format!(
+            "hotkey binding '{input}' invalid, skipped"
+        )));
+        return None;
+    };
+    let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    let action = match binding.action {
+        BindingAction::Dictate => Action::Dictate(SttOverrides {
+            provider: binding.stt_provider,
+            model: non_empty(&binding.stt_model),
+            language: non_empty(&binding.stt_language),
+        }),
+        BindingAction::Speak => {
+            let Some(message) = non_empty(&binding.message) else {
+                let _ = events.send(AppEvent::Warning(format!(
+                    "hotkey binding '{input}' has no message to speak, skipped"
+                )));
+                return None;
             };
-            update_key_state(&mut st, &event.event_type);
-            let now_active = is_hotkey_active(&st, &spec);
-            let mut was_active = match cb_active.lock() {
-                Ok(v) => v,
-                Err(_) => return,
+            Action::Speak(SpeakRequest {
+                message,
+                persona: non_empty(&binding.persona),
+                voice: None,
+                provider: None,
+                show_text: None,
+                style: None,
+            })
+        }
+        BindingAction::SpeakLast => Action::SpeakLast(SpeakRequest {
+            message: String::new(),
+            persona: non_empty(&binding.persona),
+            voice: None,
+            provider: None,
+            show_text: None,
+            style: None,
+        }),
+        BindingAction::Cancel => Action::Cancel,
+    };
+    let _ = events.send(AppEvent::Info(format!(
+        "hotkey binding active: {input} -> {}",
+        binding.action.label()
+    )));
+    Some(Binding { spec, action })
+}
+
+// Ctrl+Shift+D also satisfies a plain Ctrl+Shift binding, so among dictate bindings
+// pressed together the one with more required keys wins.
+fn most_specific_dictate(bindings: &[Binding], pressed: &[bool]) -> Option<usize> {
+    bindings
+        .iter()
+        .zip(pressed)
+        .enumerate()
+        .filter(|(_, (binding, pressed))| **pressed && matches!(binding.action, Action::Dictate(_)))
+        .max_by_key(|(index, (binding, _))| (binding.spec.specificity(), Reverse(*index)))
+        .map(|(index, _)| index)
+}
+
+impl HotkeySpec {
+    fn specificity(&self) -> usize {
+        [
+            self.require_ctrl,
+            self.require_shift,
+            self.require_alt,
+            self.require_meta,
+            self.key.is_some(),
+            self.button.is_some(),
+        ]
+        .into_iter()
+        .filter(|required| *required)
+        .count()
+    }
+}
+
+#[derive(Clone, Copy)]
+enum CaptureState {
+    Idle,
+    Holding {
+        pressed_at: Instant,
+    },
+    // Double-tap mode: a quick tap keeps recording briefly in case a second tap latches it.
+    TapPending {
+        released_at: Instant,
+    },
+    // Hold released, but recording continues briefly in case the chord comes back.
+    GracePending {
+        pressed_at: Instant,
+        released_at: Instant,
+    },
+    Latched,
+    // Started by `POST /listen`; ends when the server clears the remote flag.
+    Remote,
+    // Wait for the hotkey to be released before arming again.
+    AwaitRelease,
+}
+
+struct CaptureSession {
+    events: Sender<AppEvent>,
+    recorder: Arc<AudioRecorder>,
+    stt_tx: Sender<SttJob>,
+    tts_tx: Sender<TtsRequest>,
+    status: Arc<Mutex<Status>>,
+    interlock: PlaybackInterlock,
+    // VAD segments go through here so they pick up the active binding's overrides.
+    segment_tx: Sender<Vec<i16>>,
+    overrides: Arc<Mutex<SttOverrides>>,
+    mode: HotkeyMode,
+    double_tap: Duration,
+    min_capture: Duration,
+    // Bounce guard: presses this soon after a capture ends don't start a new one.
+    cooldown: Duration,
+    release_grace: Duration,
+    ended_at: Option<Instant>,
+    cue_sounds: bool,
+    duck_tts: ListenDuck,
+    state: CaptureState,
+    // Index of the dictate binding that owns the current (or next) capture.
+    driver: usize,
+    hotkey_was_down: bool,
+    // Whether the driving binding's keys were down at the last key update.
+    keys_down: bool,
+    triggers: Triggers,
+    ui_was_held: bool,
+    remote_was_held: bool,
+    was_down: Vec<bool>,
+}
+
+impl CaptureSession {
+    fn new(
+        events: Sender<AppEvent>,
+        recorder: Arc<AudioRecorder>,
+        stt_tx: Sender<SttJob>,
+        tts_tx: Sender<TtsRequest>,
+        status: Arc<Mutex<Status>>,
+        interlock: PlaybackInterlock,
+        triggers: Triggers,
+    ) -> Self {
+        // A worker that crashed mid-capture would otherwise leave speech held.
+        interlock.release();
+        let overrides = Arc::new(Mutex::new(SttOverrides::default()));
+        let (segment_tx, segment_rx) = unbounded::<Vec<i16>>();
+        {
+            let overrides = overrides.clone();
+            let stt_tx = stt_tx.clone();
+            thread::spawn(move || {
+                while let Ok(samples) = segment_rx.recv() {
+                    let overrides = overrides.lock().map(|o| o.clone()).unwrap_or_default();
+                    let _ = stt_tx.send(SttJob {
+                        overrides,
+                        ..SttJob::new(samples)
+                    });
+                }
+            });
+        }
+        Self {
+            events,
+            recorder,
+            stt_tx,
+            tts_tx,
+            status,
+            interlock,
+            segment_tx,
+            overrides,
+            mode: HotkeyMode::Hold,
+            double_tap: Duration::from_millis(300),
+            min_capture: Duration::from_millis(200),
+            cooldown: Duration::ZERO,
+            release_grace: Duration::ZERO,
+            ended_at: None,
+            cue_sounds: false,
+            duck_tts: ListenDuck::Off,
+            state: CaptureState::Idle,
+            driver: 0,
+            hotkey_was_down: false,
+            keys_down: false,
+            triggers,
+            ui_was_held: false,
+            remote_was_held: false,
+            was_down: Vec::new(),
+        }
+    }
+
+    fn configure(&mut self, specs: &HotkeySpecs) {
+        self.mode = specs.mode;
+        self.double_tap = specs.double_tap;
+        self.min_capture = specs.min_capture;
+        self.cooldown = specs.cooldown;
+        self.release_grace = specs.release_grace;
+        self.cue_sounds = specs.cue_sounds;
+        self.duck_tts = specs.duck_tts;
+        self.recorder.set_vad(specs.vad, &self.segment_tx);
+    }
+
+    // `down[i]` is whether `bindings[i]` is currently held.
+    fn update(&mut self, bindings: &[Binding], down: &[bool]) {
+        self.was_down.resize(down.len(), false);
+        let pressed: Vec<bool> = down
+            .iter()
+            .zip(&self.was_down)
+            .map(|(down, was)| *down && !*was)
+            .collect();
+        self.was_down.copy_from_slice(down);
+
+        for (binding, pressed) in bindings.iter().zip(&pressed) {
+            if !*pressed {
+                continue;
+            }
+            let speak = match &binding.action {
+                Action::Speak(speak) => speak.clone(),
+                Action::SpeakLast(template) => {
+                    let last = self
+                        .status
+                        .lock()
+                        .map(|status| status.last_transcript.trim().to_string())
+                        .unwrap_or_default();
+                    if last.is_empty() {
+                        let _ = self
+                            .events
+                            .send(AppEvent::Info("no transcript to speak yet".to_string()));
+                        continue;
+                    }
+                    SpeakRequest {
+                        message: last,
+                        ..template.clone()
+                    }
+                }
+                _ => continue,
             };
+            let _ = self.tts_tx.send(TtsRequest {
+                speak,
+                progress: None,
+            });
+        }
+        let cancel_down = bindings
+            .iter()
+            .zip(down)
+            .any(|(binding, down)| matches!(binding.action, Action::Cancel) && *down);
 
-            if !*was_active && now_active {
-                cb_recorder.start_capture();
-                let _ = cb_events.send(AppEvent::Listening(true));
-                *was_active = true;
-            } else if *was_active && !now_active {
-                let audio = cb_recorder.stop_capture();
-                let _ = cb_events.send(AppEvent::Listening(false));
-                if !audio.is_empty() {
-                    let _ = cb_stt_tx.send(audio);
+        // A more specific binding pressed while holding a broader one (ctrl+shift, then D)
+        // takes over the capture that has already started.
+        if let Some(index) = most_specific_dictate(bindings, &pressed) {
+            let current = bindings
+                .get(self.driver)
+                .map_or(0, |binding| binding.spec.specificity());
+            let take = match self.state {
+                CaptureState::Idle => true,
+                CaptureState::Holding { .. } => {
+                    index != self.driver && bindings[index].spec.specificity() > current
+                }
+                _ => false,
+            };
+            if take {
+                self.hotkey_was_down = !matches!(self.state, CaptureState::Idle);
+                self.driver = index;
+                if let Action::Dictate(overrides) = &bindings[index].action
+                    && let Ok(mut active) = self.overrides.lock()
+                {
+                    *active = overrides.clone();
                 }
-                *was_active = false;
             }
-        });
+        }
+        self.keys_down = bindings
+            .get(self.driver)
+            .is_some_and(|binding| matches!(binding.action, Action::Dictate(_)))
+            && down.get(self.driver).copied().unwrap_or(false);
+        self.poll_remote();
+        let ui_held = self.poll_ui_button();
+        self.update_capture(self.keys_down || ui_held, cancel_down);
+    }
 
-        if let Err(e) = result {
-            let _ = events.send(AppEvent::Error(format!("hotkey listener failed: {e:?}")));
+    // Hotkeys leave a remote capture alone (the session isn't idle), and a cancel
+    // hotkey discards it like any other.
+    fn poll_remote(&mut self) {
+        let held = self.triggers.remote_held.load(Ordering::Relaxed);
+        if held == self.remote_was_held {
+            return;
+        }
+        self.remote_was_held = held;
+        match self.state {
+            CaptureState::Idle if held => {
+                self.driver = 0;
+                if let Ok(mut active) = self.overrides.lock() {
+                    *active = SttOverrides::default();
+                }
+                self.begin_recording();
+                self.state = CaptureState::Remote;
+            }
+            CaptureState::Remote if !held => {
+                self.end_capture(true);
+                self.state = CaptureState::Idle;
+            }
+            _ => {}
         }
+    }
+
+    // A press from idle always drives the main hotkey's binding with its default overrides.
+    fn poll_ui_button(&mut self) -> bool {
+        let held = self.triggers.ui_held.load(Ordering::Relaxed);
+        if held && !self.ui_was_held && matches!(self.state, CaptureState::Idle) {
+            self.driver = 0;
+            if let Ok(mut active) = self.overrides.lock() {
+                *active = SttOverrides::default();
+            }
         }
-    });
+        self.ui_was_held = held;
+        held
+    }
+
+    fn update_capture(&mut self, hotkey_down: bool, cancel_down: bool) {
+        let pressed = hotkey_down && !self.hotkey_was_down;
+        let released = !hotkey_down && self.hotkey_was_down;
+        self.hotkey_was_down = hotkey_down;
+
+        if cancel_down && self.is_capturing() {
+            self.end_capture(false);
+            let _ = self
+                .events
+                .send(AppEvent::Info("capture discarded".to_string()));
+            self.state = if hotkey_down {
+                CaptureState::AwaitRelease
+            } else {
+                CaptureState::Idle
+            };
+            return;
+        }
+
+        match self.state {
+            CaptureState::Idle if pressed && !cancel_down => {
+                match self.ended_at.map(|at| at.elapsed()) {
+                    Some(since) if since < self.cooldown => {
+                        let _ = self.events.send(AppEvent::Info(format!(
+                            "activation ignored: {} ms after the last capture (cooldown {} ms)",
+                            since.as_millis(),
+                            self.cooldown.as_millis()
+                        )));
+                    }
+                    _ => self.start_capture(),
+                }
+            }
+            CaptureState::Holding { pressed_at } if released => {
+                if self.mode == HotkeyMode::DoubleTapLatch
+                    && pressed_at.elapsed() <= self.double_tap
+                {
+                    self.state = CaptureState::TapPending {
+                        released_at: Instant::now(),
+                    };
+                } else if !self.release_grace.is_zero() {
+                    self.state = CaptureState::GracePending {
+                        pressed_at,
+                        released_at: Instant::now(),
+                    };
+                } else {
+                    self.end_capture(true);
+                    self.state = CaptureState::Idle;
+                }
+            }
+            CaptureState::GracePending {
+                pressed_at,
+                released_at,
+            } if pressed => {
+                self.state = CaptureState::Holding { pressed_at };
+                let _ = self.events.send(AppEvent::Info(format!(
+                    "capture resumed after a {} ms release",
+                    released_at.elapsed().as_millis()
+                )));
+            }
+            CaptureState::TapPending { .. } if pressed => {
+                self.state = CaptureState::Latched;
+                let _ = self.events.send(AppEvent::CaptureLatched(true));
+                let _ = self.events.send(AppEvent::Info(
+                    "recording latched (double-tap); tap hotkey again to stop".to_string(),
+                ));
+            }
+            CaptureState::Latched if pressed => {
+                self.end_capture(true);
+                self.state = CaptureState::AwaitRelease;
+            }
+            CaptureState::AwaitRelease if !hotkey_down => self.state = CaptureState::Idle,
+            _ => {}
+        }
+    }
+
+    fn tick(&mut self) {
+        // Key updates only arrive with input events, so button changes are picked up here too.
+        self.poll_remote();
+        if self.triggers.ui_held.load(Ordering::Relaxed) != self.ui_was_held {
+            let ui_held = self.poll_ui_button();
+            self.update_capture(self.keys_down || ui_held, false);
+        }
+        let expired = match self.state {
+            CaptureState::TapPending { released_at } => released_at.elapsed() > self.double_tap,
+            CaptureState::GracePending { released_at, .. } => {
+                released_at.elapsed() > self.release_grace
+            }
+            _ => false,
+        };
+        if expired {
+            self.end_capture(true);
+            self.state = CaptureState::Idle;
+        }
+    }
+
+    fn is_capturing(&self) -> bool {
+        matches!(
+            self.state,
+            CaptureState::Holding { .. }
+                | CaptureState::TapPending { .. }
+                | CaptureState::GracePending { .. }
+                | CaptureState::Latched
+                | CaptureState::Remote
+        )
+    }
+
+    fn begin_recording(&mut self) {
+        self.recorder.start_capture();
+        self.interlock.hold(self.duck_tts);
+        let _ = self.events.send(AppEvent::Listening(true));
+        if self.cue_sounds {
+            audio::play_cue(Cue::Start);
+        }
+    }
+
+    fn start_capture(&mut self) {
+        self.begin_recording();
+        self.state = if self.mode == HotkeyMode::Toggle {
+            let _ = self.events.send(AppEvent::CaptureLatched(true));
+            let _ = self.events.send(AppEvent::Info(
+                "recording toggled on; press hotkey again to stop".to_string(),
+            ));
+            CaptureState::Latched
+        } else {
+            CaptureState::Holding {
+                pressed_at: Instant::now(),
+            }
+        };
+    }
+
+    fn end_capture(&mut self, send: bool) {
+        self.ended_at = Some(Instant::now());
+        self.interlock.release();
+        if matches!(self.state, CaptureState::Latched) {
+            let _ = self.events.send(AppEvent::CaptureLatched(false));
+        }
+        if self.cue_sounds {
+            audio::play_cue(Cue::Stop);
+        }
+        if !send {
+            self.recorder.abort_capture();
+            let _ = self.events.send(AppEvent::Listening(false));
+            return;
+        }
+        let elapsed = self.recorder.capture_elapsed();
+        let audio = self.recorder.stop_capture();
+        let _ = self.events.send(AppEvent::Listening(false));
+        if elapsed < self.min_capture {
+            let _ = self
+                .events
+                .send(AppEvent::Info("capture too short, ignored".to_string()));
+            return;
+        }
+        if !audio.is_empty() {
+            let overrides = self.overrides.lock().map(|o| o.clone()).unwrap_or_default();
+            let _ = self.stt_tx.send(SttJob {
+                overrides,
+                ..SttJob::new(audio)
+            });
+        }
+    }
 }
 
 #[cfg(target_os = "windows")]
 fn run_windows_hotkey_loop(
-    spec: HotkeySpec,
+    mut specs: HotkeySpecs,
+    config: Arc<Mutex<AppConfig>>,
+    reload_rx: Receiver<()>,
     events: Sender<AppEvent>,
-    recorder: Arc<AudioRecorder>,
-    stt_tx: Sender<Vec<i16>>,
+    mut session: CaptureSession,
 ) {
     let _ = events.send(AppEvent::Info(
         "hotkey backend: windows key-state polling".to_string(),
     ));
-    let mut was_active = false;
 
     loop {
-        let now_active = is_hotkey_active_windows(&spec);
-        if !was_active && now_active {
-            recorder.start_capture();
-            let _ = events.send(AppEvent::Listening(true));
-            was_active = true;
-        } else if was_active && !now_active {
-            let audio = recorder.stop_capture();
-            let _ = events.send(AppEvent::Listening(false));
-            if !audio.is_empty() {
-                let _ = stt_tx.send(audio);
-            }
-            was_active = false;
+        if reload_rx.try_iter().count() > 0 {
+            specs = load_specs(&config, &events);
+            session.configure(&specs);
         }
-        thread::sleep(std::time::Duration::from_millis(12));
+        let down: Vec<bool> = specs
+            .bindings
+            .iter()
+            .map(|binding| is_hotkey_active_windows(&binding.spec))
+            .collect();
+        session.update(&specs.bindings, &down);
+        session.tick();
+        thread::sleep(Duration::from_millis(12));
     }
 }
 
+fn parse_cancel_spec(input: &str, events: &Sender<AppEvent>) -> Option<HotkeySpec> {
+    if input.trim().is_empty() {
+        return None;
+    }
+    let spec = parse_supported_spec(input, events);
+    match &spec {
+        Some(_) => {
+            let _ = events.send(AppEvent::Info(format!("cancel hotkey active: {input}")));
+        }
+        None => {
+            let _ = events.send(AppEvent::Warning(format!(
+                "cancel hotkey '{input}' invalid, cancel disabled"
+            )));
+        }
+    }
+    spec
+}
+
+fn parse_supported_spec(input: &str, events: &Sender<AppEvent>) -> Option<HotkeySpec> {
+    let spec = parse_hotkey_spec(input)?;
+    if spec.button.is_some() && !MOUSE_BUTTONS_SUPPORTED {
+        let _ = events.send(AppEvent::Warning(format!(
+            "hotkey '{input}' uses a mouse button, which this platform's hotkey backend cannot detect"
+        )));
+        return None;
+    }
+    Some(spec)
+}
+
 fn parse_hotkey_spec(input: &str) -> Option<HotkeySpec> {
     let mut spec = HotkeySpec {
         require_ctrl: false,
@@ -149,6 +816,7 @@ fn parse_hotkey_spec(input: &str) -> Option<HotkeySpec> {
         require_alt: false,
         require_meta: false,
         key: None,
+        button: None,
     };
 
     for token in input.split('+').map(|s| s.trim().to_lowercase()) {
@@ -159,6 +827,10 @@ fn parse_hotkey_spec(input: &str) -> Option<HotkeySpec> {
             "win" | "window" | "meta" | "super" => spec.require_meta = true,
             "space" => spec.key = Some(Key::Space),
             "enter" => spec.key = Some(Key::Return),
+            "esc" | "escape" => spec.key = Some(Key::Escape),
+            "middle" | "mouse3" => spec.button = Some(MouseButton::Middle),
+            "mouse4" | "xbutton1" | "back" => spec.button = Some(MouseButton::X1),
+            "mouse5" | "xbutton2" | "forward" => spec.button = Some(MouseButton::X2),
             "backtick" | "grave" => spec.key = Some(Key::BackQuote),
             _ if token.len() == 1 => {
                 if let Some(ch) = token.chars().next() {
@@ -171,6 +843,7 @@ fn parse_hotkey_spec(input: &str) -> Option<HotkeySpec> {
 
     if !(spec.require_ctrl || spec.require_shift || spec.require_alt || spec.require_meta)
         && spec.key.is_none()
+        && spec.button.is_none()
     {
         return None;
     }
@@ -235,6 +908,16 @@ fn update_key_state(state: &mut KeyState, event: &EventType) {
                 state.pressed_non_mod.remove(key);
             }
         }
+        EventType::ButtonPress(button) => {
+            if let Some(button) = map_rdev_button(*button) {
+                state.pressed_buttons.insert(button);
+            }
+        }
+        EventType::ButtonRelease(button) => {
+            if let Some(button) = map_rdev_button(*button) {
+                state.pressed_buttons.remove(&button);
+            }
+        }
         _ => {}
     }
 }
@@ -279,12 +962,55 @@ fn is_hotkey_active(state: &KeyState, spec: &HotkeySpec) -> bool {
     if spec.require_meta && !state.meta {
         return false;
     }
-    if let Some(key) = spec.key {
-        return state.pressed_non_mod.contains(&key);
+    if let Some(key) = spec.key
+        && !state.pressed_non_mod.contains(&key)
+    {
+        return false;
+    }
+    if let Some(button) = spec.button
+        && !state.pressed_buttons.contains(&button)
+    {
+        return false;
     }
     true
 }
 
+// rdev's errors name the failing OS call; say what the user has to change instead.
+#[cfg(not(target_os = "windows"))]
+fn listen_failure_message(error: &rdev::ListenError) -> String {
+    use rdev::ListenError;
+    match error {
+        ListenError::EventTapError | ListenError::LoopSourceError => {
+            "global hotkeys are blocked: grant Accessibility permission to Push2Type \
+             (System Settings > Privacy & Security > Accessibility), then restart the app"
+                .to_string()
+        }
+        ListenError::MissingDisplayError => {
+            "global hotkeys need an X11 display and none was found; \
+             on Wayland, run the app under XWayland with DISPLAY set"
+                .to_string()
+        }
+        ListenError::XRecordExtensionError
+        | ListenError::RecordContextEnablingError
+        | ListenError::RecordContextError => {
+            "global hotkeys need the X11 RECORD extension, which this X server doesn't provide"
+                .to_string()
+        }
+        other => format!("hotkey listener failed: {other:?}"),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn map_rdev_button(button: Button) -> Option<MouseButton> {
+    // X11 reports the side buttons as 8/9 (4-7 are scroll).
+    match button {
+        Button::Middle => Some(MouseButton::Middle),
+        Button::Unknown(8) => Some(MouseButton::X1),
+        Button::Unknown(9) => Some(MouseButton::X2),
+        _ => None,
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn is_hotkey_active_windows(spec: &HotkeySpec) -> bool {
     if spec.require_ctrl && !(is_vk_down(VK_LCONTROL as i32) || is_vk_down(VK_RCONTROL as i32)) {
@@ -300,10 +1026,20 @@ fn is_hotkey_active_windows(spec: &HotkeySpec) -> bool {
         return false;
     }
     if let Some(key) = spec.key {
-        if let Some(vk) = key_to_vk(key) {
-            return is_vk_down(vk);
+        match key_to_vk(key) {
+            Some(vk) if is_vk_down(vk) => {}
+            _ => return false,
+        }
+    }
+    if let Some(button) = spec.button {
+        let vk = match button {
+            MouseButton::Middle => VK_MBUTTON,
+            MouseButton::X1 => VK_XBUTTON1,
+            MouseButton::X2 => VK_XBUTTON2,
+        };
+        if !is_vk_down(vk as i32) {
+            return false;
         }
-        return false;
     }
     true
 }
@@ -319,6 +1055,7 @@ fn key_to_vk(key: Key) -> Option<i32> {
     match key {
         Key::Space => Some(VK_SPACE as i32),
         Key::Return => Some(VK_RETURN as i32),
+        Key::Escape => Some(VK_ESCAPE as i32),
         Key::BackQuote => Some(VK_OEM_3 as i32),
         Key::KeyA => Some('A' as i32),
         Key::KeyB => Some('B' as i32),
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use reqwest::blocking::{Client, Response};
+
+use crate::config::Provider;
+
+// Blocking client for the STT/TTS providers. Rebuilt when `http_timeout_secs`
+// changes so a config save applies to the next request without a restart.
+pub struct TimedClient {
+    timeout_secs: u64,
+    client: Client,
+}
+
+impl TimedClient {
+    pub fn new(timeout_secs: u64) -> Self {
+        Self {
+            timeout_secs,
+            client: client(timeout_secs),
+        }
+    }
+
+    pub fn get(&mut self, timeout_secs: u64) -> &Client {
+        if timeout_secs != self.timeout_secs {
+            *self = Self::new(timeout_secs);
+        }
+        &self.client
+    }
+}
+
+pub fn client(timeout_secs: u64) -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.max(1)))
+        .build()
+        .unwrap_or_else(|_| Client::new())
+}
+
+pub fn is_timeout(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+        .any(reqwest::Error::is_timeout)
+}
+
+// Failed provider call as "OpenAI: insufficient_quota - You exceeded your quota (HTTP 429)".
+// OpenAI, Groq and xAI send `{"error":{"message","type","code"}}`, ElevenLabs sends
+// `{"detail":{"status","message"}}`; anything else is shown as the raw body.
+pub fn provider_error(provider: Provider, response: Response) -> anyhow::Error {
+    let status = response.status();
+    let body = response.text().unwrap_or_default();
+    let reason = error_reason(&body).unwrap_or_else(|| match body.trim() {
+        "" => "no details in the response".to_string(),
+        raw => raw.to_string(),
+    });
+    anyhow::anyhow!(
+        "{}: {reason} (HTTP {})",
+        provider_label(provider),
+        status.as_u16()
+    )
+}
+
+fn error_reason(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error").or_else(|| value.get("detail"))?;
+    if let Some(message) = error.as_str() {
+        return Some(message.to_string());
+    }
+    let message = error.get("message").and_then(|m| m.as_str())?;
+    let code = ["code", "type", "status"]
+        .iter()
+        .find_map(|key| error.get(*key).and_then(|c| c.as_str()));
+    Some(match code {
+        Some(code) => format!("{code} - {message}"),
+        None => message.to_string(),
+    })
+}
+
+fn provider_label(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Xai => "xAI",
+        Provider::OpenAi => "OpenAI",
+        Provider::Groq => "Groq",
+        Provider::ElevenLabs => "ElevenLabs",
+    }
+}
+
+// reqwest's own message buries the timeout behind the URL; lead with it instead.
+pub fn describe(err: &anyhow::Error, timeout_secs: u64) -> String {
+    if is_timeout(err) {
+        format!("provider did not respond within {timeout_secs}s (http_timeout_secs)")
+    } else {
+        err.to_string()
+    }
+}
@@ -1,8 +1,15 @@
-use std::{thread, time::Duration};
+use std::{
+    io::{Read, Write},
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
 
 use anyhow::Context;
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 
+use crate::config::InjectPlacement;
+
 pub struct TextInjector;
 
 impl TextInjector {
@@ -10,17 +17,274 @@ impl TextInjector {
         Self
     }
 
-    pub fn inject_text(&self, text: &str) -> anyhow::Result<()> {
+    // Returns false when `verify` is on and the clipboard still didn't hold `text`
+    // after one retry; the paste is sent anyway since it may yet land.
+    pub fn inject_text(
+        &self,
+        text: &str,
+        paste_shortcut: &str,
+        settle: Duration,
+        verify: bool,
+        placement: InjectPlacement,
+    ) -> anyhow::Result<bool> {
+        let (modifiers, key) = parse_paste_shortcut(paste_shortcut)?;
+        self.copy_text(text)?;
+        thread::sleep(settle);
+        let mut verified = true;
+        if verify && !clipboard_holds(text) {
+            // Slow clipboard listeners (Electron apps) can miss the first set.
+            self.copy_text(text)?;
+            thread::sleep(settle);
+            verified = clipboard_holds(text);
+        }
+
+        let mut enigo = Enigo::new(&Settings::default()).context("enigo init failed")?;
+        if placement == InjectPlacement::EndOfLine {
+            end_of_line(&mut enigo)?;
+        }
+        for modifier in &modifiers {
+            enigo.key(*modifier, Direction::Press)?;
+        }
+        enigo.key(key, Direction::Click)?;
+        for modifier in modifiers.iter().rev() {
+            enigo.key(*modifier, Direction::Release)?;
+        }
+        Ok(verified)
+    }
+
+    // Submits what was just pasted; the wait lets the target app finish handling the paste.
+    pub fn press_enter(&self, after: Duration) -> anyhow::Result<()> {
+        thread::sleep(after);
+        let mut enigo = Enigo::new(&Settings::default()).context("enigo init failed")?;
+        enigo.key(Key::Return, Direction::Click)?;
+        Ok(())
+    }
+
+    pub fn copy_text(&self, text: &str) -> anyhow::Result<()> {
         let mut clipboard = arboard::Clipboard::new().context("clipboard init failed")?;
         clipboard
             .set_text(text.to_string())
             .context("clipboard set failed")?;
-        thread::sleep(Duration::from_millis(85));
+        Ok(())
+    }
+}
 
-        let mut enigo = Enigo::new(&Settings::default()).context("enigo init failed")?;
-        enigo.key(Key::Control, Direction::Press)?;
-        enigo.key(Key::Unicode('v'), Direction::Click)?;
-        enigo.key(Key::Control, Direction::Release)?;
+// macOS text fields treat End as "scroll to bottom"; Cmd+Right is end of line there.
+fn end_of_line(enigo: &mut Enigo) -> anyhow::Result<()> {
+    if cfg!(target_os = "macos") {
+        enigo.key(Key::Meta, Direction::Press)?;
+        enigo.key(Key::RightArrow, Direction::Click)?;
+        enigo.key(Key::Meta, Direction::Release)?;
+    } else {
+        enigo.key(Key::End, Direction::Click)?;
+    }
+    Ok(())
+}
+
+fn clipboard_holds(text: &str) -> bool {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .is_ok_and(|current| current == text)
+}
+
+// Runs `command` through the platform shell with `text` on stdin and returns its stdout,
+// minus trailing newlines. A non-zero exit, empty output or running past `timeout` is an
+// error (the process is killed), so the caller can fall back to the raw transcript.
+pub fn pipe_through_command(
+    command: &str,
+    text: &str,
+    timeout: Duration,
+) -> anyhow::Result<String> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        shell.creation_flags(CREATE_NO_WINDOW);
+    }
+    let mut child = shell
+        .spawn()
+        .with_context(|| format!("failed to start '{command}'"))?;
+
+    // Feed and drain on their own threads so a chatty command can't fill a pipe and stall.
+    let mut stdin = child.stdin.take().context("command stdin unavailable")?;
+    let input = text.to_string();
+    thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let mut stdout = child.stdout.take().context("command stdout unavailable")?;
+    let reader = thread::spawn(move || {
+        let mut out = String::new();
+        stdout.read_to_string(&mut out).map(|_| out)
+    });
+    let mut stderr = child.stderr.take().context("command stderr unavailable")?;
+    let errors = thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stderr.read_to_string(&mut out);
+        out
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow::anyhow!(
+                "command timed out after {} ms",
+                timeout.as_millis()
+            ));
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    let output = reader
+        .join()
+        .map_err(|_| anyhow::anyhow!("command output reader panicked"))??;
+    if !status.success() {
+        let stderr = errors.join().unwrap_or_default();
+        return Err(anyhow::anyhow!(
+            "command exited with {status}: {}",
+            stderr.trim()
+        ));
+    }
+    let output = output.trim_end_matches(['\r', '\n']);
+    if output.trim().is_empty() {
+        return Err(anyhow::anyhow!("command printed nothing"));
+    }
+    Ok(output.to_string())
+}
+
+// Ok(()) when pasting is allowed; otherwise the reason it was blocked. An empty
+// allowlist allows everything. Matching is a case-insensitive title substring.
+pub fn check_window_allowlist(allowlist: &[String]) -> Result<(), String> {
+    if allowlist.is_empty() {
+        return Ok(());
+    }
+    let Some(title) = foreground_window_title() else {
+        return Err("active window title unavailable".to_string());
+    };
+    let lowered = title.to_lowercase();
+    if allowlist
+        .iter()
+        .any(|entry| lowered.contains(&entry.to_lowercase()))
+    {
         Ok(())
+    } else {
+        Err(format!("window not allowed: {title}"))
+    }
+}
+
+// Releasing a global hotkey can race Windows handing focus back to the target app.
+// Returns once the foreground window has been the same for `stable`, or false if it
+// was still changing when `timeout` ran out.
+#[cfg(target_os = "windows")]
+pub fn wait_for_stable_focus(stable: Duration, timeout: Duration) -> bool {
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    if stable.is_zero() {
+        return true;
+    }
+    let started = Instant::now();
+    let mut current = unsafe { GetForegroundWindow() };
+    let mut since = started;
+    loop {
+        if since.elapsed() >= stable {
+            return true;
+        }
+        if started.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+        let next = unsafe { GetForegroundWindow() };
+        if next != current {
+            current = next;
+            since = Instant::now();
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn wait_for_stable_focus(_stable: Duration, _timeout: Duration) -> bool {
+    true
+}
+
+#[cfg(target_os = "windows")]
+fn foreground_window_title() -> Option<String> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW};
+
+    let mut buf = [0u16; 512];
+    let len = unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32)
+    };
+    if len <= 0 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&buf[..len as usize]))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn foreground_window_title() -> Option<String> {
+    None
+}
+
+pub fn default_paste_shortcut() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "cmd+v"
+    } else {
+        "ctrl+v"
+    }
+}
+
+// "ctrl+shift+v" -> ([Control, Shift], Unicode('v')); the last token is the key.
+pub fn parse_paste_shortcut(spec: &str) -> anyhow::Result<(Vec<Key>, Key)> {
+    let tokens: Vec<String> = spec
+        .split('+')
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let Some((last, mods)) = tokens.split_last() else {
+        return Err(anyhow::anyhow!("paste shortcut is empty"));
+    };
+    let mut modifiers = Vec::new();
+    for token in mods {
+        let key = match token.as_str() {
+            "ctrl" | "control" => Key::Control,
+            "shift" => Key::Shift,
+            "alt" | "option" => Key::Alt,
+            "cmd" | "meta" | "super" | "win" => Key::Meta,
+            other => return Err(anyhow::anyhow!("unknown paste modifier '{other}'")),
+        };
+        modifiers.push(key);
     }
+    let key = match last.as_str() {
+        #[cfg(not(target_os = "macos"))]
+        "insert" | "ins" => Key::Insert,
+        other => {
+            let mut chars = other.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Unicode(c),
+                _ => return Err(anyhow::anyhow!("unknown paste key '{other}'")),
+            }
+        }
+    };
+    Ok((modifiers, key))
 }
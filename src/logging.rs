@@ -0,0 +1,288 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crossbeam_channel::{Receiver, Sender, bounded, never, select, unbounded};
+
+use crate::{
+    app::{AppEvent, Status},
+    config::{self, AppConfig},
+};
+
+const LOG_FILE_NAME: &str = "push2type.log";
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+const KEEP_ROTATED: usize = 3;
+// Lines a `/events` subscriber may fall behind before it is cut off.
+const FEED_BACKLOG: usize = 256;
+
+pub struct LogControl {
+    flush_tx: Sender<Sender<()>>,
+}
+
+impl LogControl {
+    // Waits (bounded) until every event queued so far has been routed and the log synced.
+    pub fn flush(&self, timeout: Duration) {
+        let (ack_tx, ack_rx) = bounded::<()>(1);
+        if self.flush_tx.send(ack_tx).is_ok() {
+            let _ = ack_rx.recv_timeout(timeout);
+        }
+    }
+}
+
+// Fan-out of every routed event as a JSON line, for `GET /events`.
+#[derive(Clone, Default)]
+pub struct EventFeed {
+    subscribers: Arc<Mutex<Vec<Sender<String>>>>,
+}
+
+impl EventFeed {
+    pub fn subscribe(&self) -> Receiver<String> {
+        let (tx, rx) = bounded(FEED_BACKLOG);
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    // A subscriber whose backlog is full is dropped rather than buffered without bound;
+    // its receiver sees the disconnect once it drains what it has.
+    fn publish(&self, event: &AppEvent) {
+        let Ok(mut subscribers) = self.subscribers.lock() else {
+            return;
+        };
+        if subscribers.is_empty() {
+            return;
+        }
+        let line = event_json(event).to_string();
+        subscribers.retain(|tx| tx.try_send(line.clone()).is_ok());
+    }
+}
+
+fn event_json(event: &AppEvent) -> serde_json::Value {
+    match event {
+        AppEvent::Info(message) => serde_json::json!({ "type": "info", "message": message }),
+        AppEvent::Warning(message) => serde_json::json!({ "type": "warning", "message": message }),
+        AppEvent::Error(message) => serde_json::json!({ "type": "error", "message": message }),
+        AppEvent::Listening(value) => serde_json::json!({ "type": "listening", "value": value }),
+        AppEvent::CaptureLatched(value) => {
+            serde_json::json!({ "type": "capture_latched", "value": value })
+        }
+        AppEvent::SttBusy(value) => serde_json::json!({ "type": "stt_busy", "value": value }),
+        AppEvent::TtsBusy(value) => serde_json::json!({ "type": "tts_busy", "value": value }),
+        AppEvent::LastTranscript(text) => serde_json::json!({ "type": "transcript", "text": text }),
+        AppEvent::Metric { stage, ms } => {
+            serde_json::json!({ "type": "metric", "stage": stage, "ms": ms })
+        }
+        AppEvent::HotkeyListenerFailed(message) => {
+            serde_json::json!({ "type": "hotkey_failed", "message": message })
+        }
+        AppEvent::SttTestResult {
+            provider,
+            seconds,
+            outcome,
+        } => serde_json::json!({
+            "type": "stt_test",
+            "provider": provider,
+            "seconds": seconds,
+            "text": outcome.as_ref().ok(),
+            "error": outcome.as_ref().err(),
+        }),
+        AppEvent::LastSpoken(text) => serde_json::json!({ "type": "spoken", "text": text }),
+        AppEvent::ServerOnline(endpoint) => {
+            serde_json::json!({ "type": "server_online", "endpoint": endpoint })
+        }
+        AppEvent::ServerOffline => serde_json::json!({ "type": "server_offline" }),
+        AppEvent::ConfigChanged => serde_json::json!({ "type": "config_changed" }),
+    }
+}
+
+// Sits between the workers and the UI: every event is forwarded unchanged, the
+// shared status is kept in sync, Info/Warning/Error lines are mirrored to disk
+// while `log_to_file` is on, and `/events` subscribers get a JSON copy.
+pub fn spawn_event_router(
+    config: Arc<Mutex<AppConfig>>,
+    status: Arc<Mutex<Status>>,
+    feed: EventFeed,
+    events_rx: Receiver<AppEvent>,
+    ui_tx: Sender<AppEvent>,
+) -> LogControl {
+    let (flush_tx, mut flush_rx) = unbounded::<Sender<()>>();
+    thread::spawn(move || {
+        let mut log = LogFile::default();
+        let route = |log: &mut LogFile, event: AppEvent| {
+            if let Ok(mut status) = status.lock() {
+                status.apply(&event);
+            }
+            feed.publish(&event);
+            let line = match &event {
+                AppEvent::Info(msg) => Some(format!("INFO: {msg}")),
+                AppEvent::Warning(msg) => Some(format!("WARN: {msg}")),
+                AppEvent::Error(msg) => Some(format!("ERR: {msg}")),
+                AppEvent::Metric { stage, ms } => Some(format!("INFO: {stage} latency: {ms}ms")),
+                _ => None,
+            };
+            let mut disabled = None;
+            if let Some(line) = line {
+                if config::lock(&config).log_to_file {
+                    disabled = log.write_line(&line);
+                } else {
+                    log.close();
+                }
+            }
+            // The UI may already be gone during shutdown; keep routing so the log is complete.
+            let _ = ui_tx.send(event);
+            // Routed by hand, not through `events_rx`: the file is already off, so this
+            // warning can't fail again and loop.
+            if let Some(e) = disabled {
+                let warning = AppEvent::Warning(format!("log file disabled: {e}"));
+                if let Ok(mut status) = status.lock() {
+                    status.apply(&warning);
+                }
+                feed.publish(&warning);
+                let _ = ui_tx.send(warning);
+            }
+        };
+        loop {
+            select! {
+                recv(events_rx) -> event => match event {
+                    Ok(event) => route(&mut log, event),
+                    Err(_) => break,
+                },
+                recv(flush_rx) -> ack => {
+                    let Ok(ack) = ack else {
+                        // LogControl dropped; stop selecting on a dead channel.
+                        flush_rx = never();
+                        continue;
+                    };
+                    for event in events_rx.try_iter() {
+                        route(&mut log, event);
+                    }
+                    log.sync();
+                    let _ = ack.send(());
+                }
+            }
+        }
+    });
+    LogControl { flush_tx }
+}
+
+#[derive(Default)]
+struct LogFile {
+    file: Option<File>,
+    written: u64,
+    failed: bool,
+}
+
+impl LogFile {
+    // Hands back the error the first time a write fails; later lines are dropped
+    // until `close` resets it.
+    fn write_line(&mut self, line: &str) -> Option<anyhow::Error> {
+        if self.failed {
+            return None;
+        }
+        let e = self.try_write_line(line).err()?;
+        self.failed = true;
+        self.file = None;
+        Some(e)
+    }
+
+    fn try_write_line(&mut self, line: &str) -> anyhow::Result<()> {
+        if self.file.is_none() {
+            let path = log_path()?;
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            self.written = file.metadata()?.len();
+            self.file = Some(file);
+        }
+        if self.written >= MAX_LOG_BYTES {
+            self.file = None;
+            rotate()?;
+            return self.try_write_line(line);
+        }
+        let entry = format!("{} {line}\n", timestamp_utc(SystemTime::now()));
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(entry.as_bytes())?;
+            self.written += entry.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) {
+        if let Some(file) = self.file.as_ref() {
+            let _ = file.sync_all();
+        }
+    }
+
+    fn close(&mut self) {
+        self.file = None;
+        self.failed = false;
+    }
+}
+
+pub fn log_path() -> anyhow::Result<PathBuf> {
+    let config_path = AppConfig::config_path()?;
+    let dir = config_path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("config path has no parent"))?;
+    Ok(dir.join(LOG_FILE_NAME))
+}
+
+// push2type.log -> .1 -> .2 -> .3; the oldest file falls off the end.
+fn rotate() -> anyhow::Result<()> {
+    let path = log_path()?;
+    let rotated = |n: usize| path.with_extension(format!("log.{n}"));
+    let _ = fs::remove_file(rotated(KEEP_ROTATED));
+    for n in (1..KEEP_ROTATED).rev() {
+        let from = rotated(n);
+        if from.exists() {
+            fs::rename(&from, rotated(n + 1))?;
+        }
+    }
+    fs::rename(&path, rotated(1))?;
+    Ok(())
+}
+
+fn timestamp_utc(now: SystemTime) -> String {
+    let (year, month, day, hour, minute, second, millis) = utc_parts(now);
+    format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}.{millis:03}Z")
+}
+
+// Same instant as `timestamp_utc` but safe to use in file names on Windows.
+pub fn file_timestamp(now: SystemTime) -> String {
+    let (year, month, day, hour, minute, second, millis) = utc_parts(now);
+    format!("{year:04}{month:02}{day:02}-{hour:02}{minute:02}{second:02}-{millis:03}")
+}
+
+fn utc_parts(now: SystemTime) -> (i64, u32, u32, u64, u64, u64, u32) {
+    let duration = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = duration.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    (
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem / 60) % 60,
+        rem % 60,
+        duration.subsec_millis(),
+    )
+}
+
+// Days since 1970-01-01 to a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -3,15 +3,27 @@
 mod app;
 mod audio;
 mod config;
+mod diagnostics;
+mod dsp;
+mod flac;
 mod hotkey;
+mod http;
 mod inject;
+mod logging;
 mod server;
 mod stt;
+mod supervisor;
+mod transcript;
+#[cfg(windows)]
+mod tray;
 mod tts;
 
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use app::{AppEvent, Push2TypeApp};
+use app::{AppEvent, Push2TypeApp, Status};
 use audio::AudioRecorder;
 use config::AppConfig;
 use crossbeam_channel::unbounded;
@@ -19,11 +31,29 @@ use crossbeam_channel::unbounded;
 fn main() -> anyhow::Result<()> {
     dotenvy::dotenv().ok();
 
-    let config = AppConfig::load_or_create()?;
+    let (config, migrations) = AppConfig::load_or_create()?;
+    let missing_keys = config.check_keys();
     let shared_config = Arc::new(Mutex::new(config));
 
-    let (ui_event_tx, ui_event_rx) = unbounded::<AppEvent>();
-    let (stt_tx, stt_rx) = unbounded::<Vec<i16>>();
+    let (ui_event_tx, router_rx) = unbounded::<AppEvent>();
+    let (router_tx, ui_event_rx) = unbounded::<AppEvent>();
+    let status = Arc::new(Mutex::new(Status::default()));
+    let feed = logging::EventFeed::default();
+    let log_control = logging::spawn_event_router(
+        shared_config.clone(),
+        status.clone(),
+        feed.clone(),
+        router_rx,
+        router_tx,
+    );
+    config::report_lock_warnings(ui_event_tx.clone());
+    for migration in migrations {
+        let _ = ui_event_tx.send(AppEvent::Info(migration));
+    }
+    for warning in missing_keys {
+        let _ = ui_event_tx.send(AppEvent::Warning(warning));
+    }
+    let (stt_tx, stt_rx) = unbounded::<stt::SttJob>();
     let (tts_tx, tts_rx) = unbounded::<tts::TtsRequest>();
 
     let recorder = Arc::new(AudioRecorder::new(ui_event_tx.clone())?);
@@ -34,29 +64,45 @@ fn main() -> anyhow::Result<()> {
         stt_rx,
         Arc::new(inject::TextInjector::new()),
         recorder.sample_rate(),
+        tts_tx.clone(),
+        status.clone(),
     );
-    tts::spawn_tts_worker(shared_config.clone(), ui_event_tx.clone(), tts_rx);
-    hotkey::spawn_hotkey_worker(
+    let interlock = tts::PlaybackInterlock::default();
+    tts::spawn_tts_worker(
+        shared_config.clone(),
+        ui_event_tx.clone(),
+        tts_rx,
+        interlock.clone(),
+    );
+    let hotkey_control = hotkey::spawn_hotkey_worker(
         shared_config.clone(),
         ui_event_tx.clone(),
         recorder.clone(),
         stt_tx.clone(),
+        tts_tx.clone(),
+        status.clone(),
+        interlock,
     );
-    let (initial_tts_bridge_enabled, initial_server_port) = {
-        let cfg = shared_config.lock().expect("config lock");
-        (cfg.tts_bridge_enabled, cfg.server_port)
-    };
+    let startup = config::lock(&shared_config).clone();
     let server_control = server::spawn_server_controller(
-        initial_tts_bridge_enabled,
-        initial_server_port,
+        startup.tts_bridge_enabled,
+        startup.server_port,
+        shared_config.clone(),
+        status,
+        feed,
         ui_event_tx.clone(),
-        tts_tx.clone(),
+        server::Pipelines {
+            tts_tx: tts_tx.clone(),
+            hotkey: hotkey_control.clone(),
+        },
     );
 
     let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([480.0, 280.0])
-        .with_min_inner_size([420.0, 240.0])
-        .with_always_on_top();
+        .with_inner_size(initial_window_size(&startup))
+        .with_min_inner_size([420.0, 240.0]);
+    if startup.always_on_top {
+        viewport = viewport.with_always_on_top();
+    }
     if let Some(icon) = load_window_icon() {
         viewport = viewport.with_icon(icon);
     }
@@ -68,22 +114,75 @@ fn main() -> anyhow::Result<()> {
     eframe::run_native(
         "Push2Type Satellite (Rust)",
         native_options,
-        Box::new(move |_cc| {
-            Ok(Box::new(Push2TypeApp::new(
+        Box::new(move |cc| {
+            #[cfg(windows)]
+            let tray = tray::Tray::new(
+                cc,
+                shared_config.clone(),
+                server_control.clone(),
+                ui_event_tx,
+            );
+            #[cfg(not(windows))]
+            let _ = cc;
+            let app = Push2TypeApp::new(
                 shared_config,
                 ui_event_rx,
                 tts_tx,
                 stt_tx,
                 recorder,
                 server_control,
-            )))
+                hotkey_control,
+            );
+            #[cfg(windows)]
+            let app = app.with_tray(tray);
+            Ok(Box::new(app))
         }),
     )
     .map_err(|e| anyhow::anyhow!("eframe run failed: {e}"))?;
 
+    // The app's on_exit has already stopped the server and waited for in-flight work.
+    log_control.flush(Duration::from_secs(1));
     Ok(())
 }
 
+// With auto-resize on, the height follows the content anyway; only the width is kept.
+fn initial_window_size(cfg: &AppConfig) -> [f32; 2] {
+    let width = cfg.window_w.unwrap_or(480.0).max(420.0);
+    let height = match cfg.window_h {
+        Some(height) if !cfg.auto_resize => height.max(240.0),
+        _ => 280.0,
+    };
+    [width, height]
+}
+
+// Whether a saved window position (in points) is on a monitor; None until the window's
+// first frame reports its scale and monitor. Tested on a point just inside the title
+// bar, which has to be reachable to drag the window back.
+#[cfg(target_os = "windows")]
+fn position_visible(x: f32, y: f32, viewport: &egui::ViewportInfo) -> Option<bool> {
+    use windows_sys::Win32::{
+        Foundation::POINT,
+        Graphics::Gdi::{MONITOR_DEFAULTTONULL, MonitorFromPoint},
+    };
+    // MonitorFromPoint takes physical pixels; the move to (x, y) is scaled by the
+    // window's own factor, so use the same one here.
+    let scale = viewport.native_pixels_per_point?;
+    let point = POINT {
+        x: ((x + 40.0) * scale) as i32,
+        y: ((y + 10.0) * scale) as i32,
+    };
+    Some(!unsafe { MonitorFromPoint(point, MONITOR_DEFAULTTONULL) }.is_null())
+}
+
+// Only the monitor the window opened on is known here, so a position anywhere else
+// falls back to the default placement.
+#[cfg(not(target_os = "windows"))]
+fn position_visible(x: f32, y: f32, viewport: &egui::ViewportInfo) -> Option<bool> {
+    let monitor = viewport.monitor_size?;
+    let (x, y) = (x + 40.0, y + 10.0);
+    Some((0.0..monitor.x).contains(&x) && (0.0..monitor.y).contains(&y))
+}
+
 fn load_window_icon() -> Option<egui::IconData> {
     let bytes = include_bytes!("../assets/app.png");
     let image = image::load_from_memory(bytes).ok()?.to_rgba8();
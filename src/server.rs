@@ -1,14 +1,24 @@
 use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex, PoisonError},
     thread::{self, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use crossbeam_channel::{Sender, unbounded};
-use tiny_http::{Header, Method, Response, Server, StatusCode};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, bounded, unbounded};
+use serde::Deserialize;
+use tiny_http::{Header, Method, Request, Response, Server, StatusCode};
+use tungstenite::{Message, WebSocket, handshake::derive_accept_key, protocol::Role};
 
 use crate::{
-    app::AppEvent,
-    tts::{SpeakRequest, TtsRequest},
+    app::{AppEvent, Status},
+    config::{self, AppConfig, Provider},
+    dsp,
+    hotkey::HotkeyControl,
+    http,
+    logging::EventFeed,
+    stt, supervisor,
+    tts::{self, SpeakRequest, TtsProgress, TtsRequest},
 };
 
 #[derive(Clone)]
@@ -24,11 +34,39 @@ impl ServerControl {
     pub fn set_port(&self, port: u16) {
         let _ = self.cmd_tx.send(ServerCommand::SetPort(port));
     }
+
+    // Stops the listener and waits (bounded) for its thread to exit.
+    pub fn shutdown(&self, timeout: Duration) {
+        let (ack_tx, ack_rx) = bounded::<()>(1);
+        if self.cmd_tx.send(ServerCommand::Shutdown(ack_tx)).is_ok() {
+            let _ = ack_rx.recv_timeout(timeout);
+        }
+    }
 }
 
 enum ServerCommand {
     SetEnabled(bool),
     SetPort(u16),
+    Shutdown(Sender<()>),
+}
+
+// The workers the server hands requests to.
+pub struct Pipelines {
+    pub tts_tx: Sender<TtsRequest>,
+    pub hotkey: HotkeyControl,
+}
+
+// Handles every server thread needs; cloned into each running instance.
+#[derive(Clone)]
+struct ServerShared {
+    config: Arc<Mutex<AppConfig>>,
+    status: Arc<Mutex<Status>>,
+    feed: EventFeed,
+    events: Sender<AppEvent>,
+    tts_tx: Sender<TtsRequest>,
+    hotkey: HotkeyControl,
+    // Stops the capture started by `POST /listen`, while one is running.
+    listen_stop: Arc<Mutex<Option<Sender<()>>>>,
 }
 
 struct RunningServer {
@@ -40,23 +78,40 @@ struct RunningServer {
 pub fn spawn_server_controller(
     initial_enabled: bool,
     initial_port: u16,
+    config: Arc<Mutex<AppConfig>>,
+    status: Arc<Mutex<Status>>,
+    feed: EventFeed,
     events: Sender<AppEvent>,
-    tts_tx: Sender<TtsRequest>,
+    pipelines: Pipelines,
 ) -> ServerControl {
     let (cmd_tx, cmd_rx) = unbounded::<ServerCommand>();
     let control = ServerControl { cmd_tx };
+    let shared = ServerShared {
+        config,
+        status,
+        feed,
+        events,
+        tts_tx: pipelines.tts_tx,
+        hotkey: pipelines.hotkey,
+        listen_stop: Arc::default(),
+    };
     thread::spawn(move || {
         let mut enabled = initial_enabled;
         let mut port = initial_port;
         let mut running = None;
-        reconcile_server_state(enabled, port, &mut running, &events, &tts_tx);
+        reconcile_server_state(enabled, port, &mut running, &shared);
 
         while let Ok(cmd) = cmd_rx.recv() {
             match cmd {
                 ServerCommand::SetEnabled(next) => enabled = next,
                 ServerCommand::SetPort(next) => port = next,
+                ServerCommand::Shutdown(ack) => {
+                    stop_server(&mut running);
+                    let _ = ack.send(());
+                    return;
+                }
             }
-            reconcile_server_state(enabled, port, &mut running, &events, &tts_tx);
+            reconcile_server_state(enabled, port, &mut running, &shared);
         }
 
         stop_server(&mut running);
@@ -68,8 +123,7 @@ fn reconcile_server_state(
     enabled: bool,
     port: u16,
     running: &mut Option<RunningServer>,
-    events: &Sender<AppEvent>,
-    tts_tx: &Sender<TtsRequest>,
+    shared: &ServerShared,
 ) {
     if !enabled {
         stop_server(running);
@@ -84,8 +138,40 @@ fn reconcile_server_state(
         return;
     }
 
+    // Bind first so a bad port leaves the current server untouched.
+    let Some(server) = bind_server(port, running.as_ref().map(|r| r.port), &shared.events) else {
+        return;
+    };
     stop_server(running);
-    *running = start_server(port, events, tts_tx);
+    *running = Some(start_server(port, server, shared));
+}
+
+fn bind_server(port: u16, current: Option<u16>, events: &Sender<AppEvent>) -> Option<Server> {
+    let addr = format!("127.0.0.1:{port}");
+    let err = match Server::http(&addr) {
+        Ok(server) => return Some(server),
+        Err(e) => e,
+    };
+    let in_use = err
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::AddrInUse);
+    let reason = if in_use {
+        format!("port {port} is already in use by another program; choose a different HTTP Port")
+    } else {
+        format!("server start failed on port {port}: {err}")
+    };
+    match current {
+        Some(active) => {
+            let _ = events.send(AppEvent::Error(format!(
+                "{reason} (still serving on port {active})"
+            )));
+        }
+        None => {
+            let _ = events.send(AppEvent::Error(reason));
+            let _ = events.send(AppEvent::ServerOffline);
+        }
+    }
+    None
 }
 
 fn stop_server(running: &mut Option<RunningServer>) {
@@ -95,28 +181,26 @@ fn stop_server(running: &mut Option<RunningServer>) {
     }
 }
 
-fn start_server(
-    port: u16,
-    events: &Sender<AppEvent>,
-    tts_tx: &Sender<TtsRequest>,
-) -> Option<RunningServer> {
+fn start_server(port: u16, server: Server, shared: &ServerShared) -> RunningServer {
     let addr = format!("127.0.0.1:{port}");
-    let server = match Server::http(&addr) {
-        Ok(s) => s,
-        Err(e) => {
-            let _ = events.send(AppEvent::Error(format!("server start failed: {e}")));
-            let _ = events.send(AppEvent::ServerOffline);
-            return None;
-        }
-    };
     let endpoint = format!("http://{addr}/speak");
-    let events_clone = events.clone();
-    let tts_tx_clone = tts_tx.clone();
+    let ServerShared {
+        config,
+        status,
+        feed,
+        events: events_clone,
+        tts_tx: tts_tx_clone,
+        hotkey,
+        listen_stop,
+    } = shared.clone();
     let (stop_tx, stop_rx) = unbounded::<()>();
 
-    let join = thread::spawn(move || {
+    let join = supervisor::spawn("server", events_clone.clone(), config.clone(), move || {
         let _ = events_clone.send(AppEvent::ServerOnline(endpoint.clone()));
         let _ = events_clone.send(AppEvent::Info(format!("endpoint online: {endpoint}")));
+        let mut speak_limit = SpeakLimiter::default();
+        // One token per running /transcribe; the handler's TranscribeSlot gives it back.
+        let (transcribe_slots, transcribe_done) = bounded::<()>(MAX_TRANSCRIBE_JOBS);
 
         loop {
             if stop_rx.try_recv().is_ok() {
@@ -132,32 +216,305 @@ fn start_server(
             let Some(mut request) = req else {
                 continue;
             };
-            match (request.method(), request.url()) {
+            let method = request.method().clone();
+            let url = request.url().to_string();
+            let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+            match (&method, path) {
                 (&Method::Get, "/health") => {
                     let body = r#"{"ok":true}"#;
                     let _ = request.respond(json_response(body, 200));
                 }
+                (&Method::Get, "/status") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let current = status.lock().map(|s| s.clone()).unwrap_or_default();
+                    let body = serde_json::json!({
+                        "listening": current.listening,
+                        "stt_busy": current.stt_busy,
+                        "tts_busy": current.tts_busy,
+                        "tts_queue": tts_tx_clone.len(),
+                    })
+                    .to_string();
+                    let _ = request.respond(json_response(&body, 200));
+                }
+                (&Method::Get, "/config") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let body = config::lock(&config).public_view().to_string();
+                    let _ = request.respond(json_response(&body, 200));
+                }
+                (&Method::Post, "/config") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let mut body = String::new();
+                    if request.as_reader().read_to_string(&mut body).is_err() {
+                        let _ = request.respond(error_response(
+                            400,
+                            "invalid_body",
+                            "request body could not be read",
+                            None,
+                        ));
+                        continue;
+                    }
+                    let patch = match serde_json::from_str::<ConfigPatch>(&body) {
+                        Ok(patch) => patch,
+                        Err(e) => {
+                            let _ = request.respond(error_response(
+                                400,
+                                "invalid_json",
+                                "body is not a valid config patch",
+                                Some(e.to_string()),
+                            ));
+                            continue;
+                        }
+                    };
+                    let mut cfg = config::lock(&config);
+                    let mut next = cfg.clone();
+                    match patch.apply(&mut next) {
+                        Ok(changed) => {
+                            if let Err(e) = next.save() {
+                                drop(cfg);
+                                let _ = request.respond(error_response(
+                                    500,
+                                    "save_failed",
+                                    "config could not be saved",
+                                    Some(e.to_string()),
+                                ));
+                                continue;
+                            }
+                            *cfg = next;
+                            let body = cfg.public_view().to_string();
+                            drop(cfg);
+                            let _ = events_clone.send(AppEvent::Info(format!(
+                                "config updated via /config: {}",
+                                changed.join(", ")
+                            )));
+                            let _ = events_clone.send(AppEvent::ConfigChanged);
+                            let _ = request.respond(json_response(&body, 200));
+                        }
+                        Err((code, message)) => {
+                            drop(cfg);
+                            let _ = request.respond(error_response(400, code, &message, None));
+                        }
+                    }
+                }
                 (&Method::Post, "/speak") => {
                     let mut body = String::new();
                     if request.as_reader().read_to_string(&mut body).is_err() {
-                        let _ = request.respond(json_response(r#"{"error":"invalid body"}"#, 400));
+                        let _ = request.respond(error_response(
+                            400,
+                            "invalid_body",
+                            "request body could not be read",
+                            None,
+                        ));
                         continue;
                     }
                     match serde_json::from_str::<SpeakRequest>(&body) {
                         Ok(speak) => {
-                            let _ = tts_tx_clone.send(TtsRequest { speak });
+                            // Checked here too so an over-limit message is a 400, not a blind 202.
+                            let fitted =
+                                tts::fit_max_chars(&config::lock(&config), speak.message.trim());
+                            if let Err(reason) = fitted {
+                                let _ = request.respond(error_response(
+                                    400,
+                                    "message_too_long",
+                                    &reason,
+                                    None,
+                                ));
+                                continue;
+                            }
+                            let rate = config::lock(&config).speak_rate_per_min;
+                            if let Err(wait) = speak_limit.take(rate) {
+                                let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+                                let _ = events_clone.send(AppEvent::Warning(format!(
+                                    "/speak rate limit hit ({rate}/min); rejected, retry in {retry_after}s"
+                                )));
+                                let response = error_response(
+                                    429,
+                                    "rate_limited",
+                                    &format!("more than {rate} speak requests per minute"),
+                                    None,
+                                )
+                                .with_header(
+                                    Header::from_bytes("Retry-After", retry_after.to_string())
+                                        .expect("numeric header"),
+                                );
+                                let _ = request.respond(response);
+                                continue;
+                            }
+                            if matches!(query_param(query, "wait"), Some("true" | "1")) {
+                                let tts_tx = tts_tx_clone.clone();
+                                thread::spawn(move || handle_speak_wait(request, speak, &tts_tx));
+                                continue;
+                            }
+                            let _ = tts_tx_clone.send(TtsRequest {
+                                speak,
+                                progress: None,
+                            });
                             let _ = request.respond(json_response(r#"{"accepted":true}"#, 202));
                         }
                         Err(e) => {
                             let _ = events_clone
                                 .send(AppEvent::Warning(format!("bad /speak request: {e}")));
-                            let _ =
-                                request.respond(json_response(r#"{"error":"invalid json"}"#, 400));
+                            let _ = request.respond(error_response(
+                                400,
+                                "invalid_json",
+                                "body is not a valid speak request",
+                                Some(e.to_string()),
+                            ));
+                        }
+                    }
+                }
+                (&Method::Get, "/stream") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let tts_tx = tts_tx_clone.clone();
+                    let events = events_clone.clone();
+                    thread::spawn(move || handle_stream(request, &tts_tx, &events));
+                }
+                (&Method::Get, "/events") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let feed = feed.subscribe();
+                    let events = events_clone.clone();
+                    thread::spawn(move || handle_events(request, feed, &events));
+                }
+                (&Method::Post, "/transcribe") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let raw_rate = query_param(query, "sample_rate")
+                        .and_then(|v| v.parse::<u32>().ok())
+                        .unwrap_or(DEFAULT_RAW_SAMPLE_RATE);
+                    if transcribe_slots.try_send(()).is_err() {
+                        let response = error_response(
+                            503,
+                            "transcribe_busy",
+                            &format!("{MAX_TRANSCRIBE_JOBS} transcriptions already running"),
+                            None,
+                        )
+                        .with_header(
+                            Header::from_bytes("Retry-After", "1").expect("numeric header"),
+                        );
+                        let _ = request.respond(response);
+                        continue;
+                    }
+                    let slot = TranscribeSlot(transcribe_done.clone());
+                    let config = config.clone();
+                    let events = events_clone.clone();
+                    // Transcription takes seconds; keep the accept loop responsive.
+                    thread::spawn(move || {
+                        let _slot = slot;
+                        handle_transcribe(request, &config, &events, raw_rate)
+                    });
+                }
+                (&Method::Post, "/listen") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
+                    }
+                    let mut body = String::new();
+                    if request.as_reader().read_to_string(&mut body).is_err() {
+                        let _ = request.respond(error_response(
+                            400,
+                            "invalid_body",
+                            "request body could not be read",
+                            None,
+                        ));
+                        continue;
+                    }
+                    let listen = if body.trim().is_empty() {
+                        Ok(ListenRequest::default())
+                    } else {
+                        serde_json::from_str::<ListenRequest>(&body)
+                    };
+                    let listen = match listen {
+                        Ok(listen) => listen,
+                        Err(e) => {
+                            let _ = request.respond(error_response(
+                                400,
+                                "invalid_json",
+                                "body is not a valid listen request",
+                                Some(e.to_string()),
+                            ));
+                            continue;
                         }
+                    };
+                    let hotkey_listening = status.lock().is_ok_and(|s| s.listening);
+                    let mut slot = listen_stop.lock().unwrap_or_else(PoisonError::into_inner);
+                    if hotkey_listening || slot.is_some() {
+                        drop(slot);
+                        let _ = request.respond(error_response(
+                            409,
+                            "already_listening",
+                            "a capture is already running",
+                            None,
+                        ));
+                        continue;
+                    }
+                    let duration = listen
+                        .duration_ms
+                        .map_or(MAX_REMOTE_LISTEN, Duration::from_millis)
+                        .min(MAX_REMOTE_LISTEN);
+                    let (stop_tx, stop_rx) = bounded::<()>(1);
+                    *slot = Some(stop_tx.clone());
+                    drop(slot);
+                    let capture = RemoteCapture {
+                        hotkey: hotkey.clone(),
+                        listen_stop: listen_stop.clone(),
+                        events: events_clone.clone(),
+                    };
+                    thread::spawn(move || capture.run(duration, stop_tx, stop_rx));
+                    let body = serde_json::json!({
+                        "accepted": true,
+                        "duration_ms": duration.as_millis() as u64,
+                    })
+                    .to_string();
+                    let _ = request.respond(json_response(&body, 202));
+                }
+                (&Method::Post, "/stop-listen") => {
+                    if !authorized(&request, query, &config) {
+                        let _ = request.respond(unauthorized());
+                        continue;
                     }
+                    let stop = listen_stop.lock().ok().and_then(|mut slot| slot.take());
+                    let response = match stop {
+                        Some(stop) => {
+                            let _ = stop.send(());
+                            json_response(r#"{"stopped":true}"#, 200)
+                        }
+                        None => error_response(
+                            409,
+                            "not_listening",
+                            "no /listen capture is running",
+                            None,
+                        ),
+                    };
+                    let _ = request.respond(response);
                 }
                 _ => {
-                    let _ = request.respond(json_response(r#"{"error":"not found"}"#, 404));
+                    let response = match allowed_methods(path) {
+                        Some(allow) => error_response(
+                            405,
+                            "method_not_allowed",
+                            &format!("{path} only accepts {allow}"),
+                            None,
+                        )
+                        .with_header(Header::from_bytes("Allow", allow).expect("static header")),
+                        None => error_response(404, "not_found", "no such endpoint", None),
+                    };
+                    let _ = request.respond(response);
                 }
             }
         }
@@ -165,13 +522,499 @@ fn start_server(
         let _ = events_clone.send(AppEvent::Info("endpoint offline".to_string()));
     });
 
-    Some(RunningServer {
+    RunningServer {
         port,
         stop_tx,
         join,
+    }
+}
+
+// Token bucket for `/speak`, shared by every client: holds up to a minute's worth of
+// requests and refills continuously, so short bursts pass but a loop can't keep talking.
+#[derive(Default)]
+struct SpeakLimiter {
+    tokens: f64,
+    refilled_at: Option<Instant>,
+}
+
+impl SpeakLimiter {
+    // Err carries how long until the next request would be let through.
+    fn take(&mut self, per_min: u32) -> Result<(), Duration> {
+        if per_min == 0 {
+            self.refilled_at = None;
+            return Ok(());
+        }
+        let capacity = per_min as f64;
+        let per_sec = capacity / 60.0;
+        let now = Instant::now();
+        self.tokens = match self.refilled_at {
+            Some(at) => {
+                (self.tokens + now.duration_since(at).as_secs_f64() * per_sec).min(capacity)
+            }
+            None => capacity,
+        };
+        self.refilled_at = Some(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+        }
+    }
+}
+
+// `POST /speak?wait=true`: answers once playback ends, so callers learn about
+// rejected requests and failed chunks instead of getting a blind 202.
+fn handle_speak_wait(request: Request, speak: SpeakRequest, tts_tx: &Sender<TtsRequest>) {
+    let (progress_tx, progress_rx) = unbounded::<TtsProgress>();
+    let queued = tts_tx.send(TtsRequest {
+        speak,
+        progress: Some(progress_tx),
+    });
+    if queued.is_err() {
+        let _ = request.respond(error_response(
+            503,
+            "tts_unavailable",
+            "tts worker unavailable",
+            None,
+        ));
+        return;
+    }
+    let mut errors = Vec::new();
+    let response = loop {
+        match progress_rx.recv_timeout(SPEAK_WAIT_TIMEOUT) {
+            Ok(TtsProgress::ChunkFailed { index, error }) => {
+                errors.push(serde_json::json!({ "index": index, "error": error }));
+            }
+            Ok(TtsProgress::Done { played, total }) => {
+                let mut body =
+                    serde_json::json!({ "played": played, "total": total, "errors": errors });
+                if played == 0 && total > 0 {
+                    body["error"] = error_body("tts_failed", "every chunk failed", None);
+                    break json_response(&body.to_string(), 502);
+                }
+                break json_response(&body.to_string(), 200);
+            }
+            Ok(TtsProgress::Rejected(error)) => {
+                break error_response(422, "tts_rejected", &error, None);
+            }
+            Ok(TtsProgress::Failed(error)) => {
+                break error_response(500, "tts_failed", &error, None);
+            }
+            Ok(_) => {}
+            Err(_) => {
+                break error_response(504, "timeout", "timed out waiting for playback", None);
+            }
+        }
+    };
+    let _ = request.respond(response);
+}
+
+// Per progress event, so a long message that keeps playing never trips it.
+const SPEAK_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+const DEFAULT_RAW_SAMPLE_RATE: u32 = 16_000;
+const MAX_TRANSCRIBE_BYTES: usize = 25 * 1024 * 1024;
+// Each /transcribe holds a decoded clip and a provider call; more than this get 503.
+const MAX_TRANSCRIBE_JOBS: usize = 2;
+
+// Frees a /transcribe slot when the handler ends, however it ends.
+struct TranscribeSlot(Receiver<()>);
+
+impl Drop for TranscribeSlot {
+    fn drop(&mut self) {
+        let _ = self.0.try_recv();
+    }
+}
+
+// Body is a WAV file, or raw 16-bit little-endian mono PCM at `?sample_rate=` (default 16 kHz).
+fn handle_transcribe(
+    mut request: Request,
+    config: &Arc<Mutex<AppConfig>>,
+    events: &Sender<AppEvent>,
+    raw_rate: u32,
+) {
+    let mut body = Vec::new();
+    let read = request
+        .as_reader()
+        .take(MAX_TRANSCRIBE_BYTES as u64 + 1)
+        .read_to_end(&mut body);
+    if read.is_err() || body.is_empty() {
+        let _ = request.respond(error_response(
+            400,
+            "invalid_body",
+            "request body could not be read",
+            None,
+        ));
+        return;
+    }
+    if body.len() > MAX_TRANSCRIBE_BYTES {
+        let _ = request.respond(error_response(
+            413,
+            "audio_too_large",
+            &format!("audio is larger than {MAX_TRANSCRIBE_BYTES} bytes"),
+            None,
+        ));
+        return;
+    }
+    let decoded = if body.starts_with(b"RIFF") {
+        dsp::decode_wav_to_i16(&body)
+    } else {
+        Ok((
+            body.chunks_exact(2)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]))
+                .collect(),
+            raw_rate,
+        ))
+    };
+    let (samples, sample_rate) = match decoded {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            let _ = request.respond(error_response(
+                400,
+                "invalid_audio",
+                "audio could not be decoded",
+                Some(e.to_string()),
+            ));
+            return;
+        }
+    };
+    let current = config::lock(config).clone();
+    let client = http::client(current.http_timeout_secs);
+    match stt::transcribe_with_provider(&client, &current, &samples, sample_rate, events) {
+        Ok(transcript) => {
+            let _ = events.send(AppEvent::Info(format!(
+                "/transcribe served by {} ({:.2}s audio)",
+                transcript.provider,
+                samples.len() as f32 / sample_rate.max(1) as f32
+            )));
+            let body = serde_json::json!({
+                "text": transcript.text,
+                "provider": transcript.provider,
+                "confidence": transcript.confidence,
+            })
+            .to_string();
+            let _ = request.respond(json_response(&body, 200));
+        }
+        Err(e) => {
+            let error = http::describe(&e, current.http_timeout_secs);
+            let _ = events.send(AppEvent::Warning(format!("/transcribe failed: {error}")));
+            let response = if http::is_timeout(&e) {
+                error_response(504, "timeout", "stt provider timed out", Some(error))
+            } else {
+                error_response(502, "stt_failed", "transcription failed", Some(error))
+            };
+            let _ = request.respond(response);
+        }
+    }
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+// Also the length of a `/listen` without `duration_ms`, unless `/stop-listen` comes first.
+const MAX_REMOTE_LISTEN: Duration = Duration::from_secs(120);
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct ListenRequest {
+    duration_ms: Option<u64>,
+}
+
+// A `/listen` capture: records until its duration is up or `/stop-listen` fires. It runs
+// in the hotkey session, so TTS ducking, cue sounds, `min_capture_ms` and the paste all
+// behave as for a hotkey release, and hotkeys can't start a second capture meanwhile.
+struct RemoteCapture {
+    hotkey: HotkeyControl,
+    listen_stop: Arc<Mutex<Option<Sender<()>>>>,
+    events: Sender<AppEvent>,
+}
+
+impl RemoteCapture {
+    fn run(self, duration: Duration, stop_tx: Sender<()>, stop_rx: Receiver<()>) {
+        self.hotkey.set_remote_held(true);
+        let _ = self.events.send(AppEvent::Info(format!(
+            "listening via /listen for up to {} ms",
+            duration.as_millis()
+        )));
+        let _ = stop_rx.recv_timeout(duration);
+        // Timed out: clear our own slot, but never one a newer /listen has taken.
+        if let Ok(mut slot) = self.listen_stop.lock()
+            && slot.as_ref().is_some_and(|tx| tx.same_channel(&stop_tx))
+        {
+            slot.take();
+        }
+        self.hotkey.set_remote_held(false);
+    }
+}
+
+// Fields agents may change at runtime; anything else is rejected.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigPatch {
+    stt_provider: Option<Provider>,
+    tts_provider: Option<Provider>,
+    xai_voice: Option<String>,
+    openai_voice: Option<String>,
+    groq_voice: Option<String>,
+    elevenlabs_voice: Option<String>,
+    xai_tts_style: Option<String>,
+}
+
+impl ConfigPatch {
+    // Errors are (code, message) for the structured error body.
+    fn apply(self, cfg: &mut AppConfig) -> Result<Vec<&'static str>, (&'static str, String)> {
+        let mut changed = Vec::new();
+        if let Some(provider) = self.stt_provider {
+            // ElevenLabs is TTS-only; the STT worker would fail every capture.
+            if provider == Provider::ElevenLabs {
+                return Err((
+                    "invalid_provider",
+                    "elevenlabs can't be used as stt_provider".to_string(),
+                ));
+            }
+            cfg.stt_provider = provider;
+            changed.push("stt_provider");
+        }
+        if let Some(provider) = self.tts_provider {
+            cfg.tts_provider = provider;
+            changed.push("tts_provider");
+        }
+        for (field, provider, voice, target) in [
+            (
+                "xai_voice",
+                Provider::Xai,
+                self.xai_voice,
+                &mut cfg.xai_voice,
+            ),
+            (
+                "openai_voice",
+                Provider::OpenAi,
+                self.openai_voice,
+                &mut cfg.openai_voice,
+            ),
+            (
+                "groq_voice",
+                Provider::Groq,
+                self.groq_voice,
+                &mut cfg.groq_voice,
+            ),
+            (
+                "elevenlabs_voice",
+                Provider::ElevenLabs,
+                self.elevenlabs_voice,
+                &mut cfg.elevenlabs_voice,
+            ),
+        ] {
+            let Some(voice) = voice else {
+                continue;
+            };
+            let voice = tts::normalize_voice(provider, &voice);
+            if !tts::is_valid_voice(provider, &voice) {
+                return Err(("invalid_voice", format!("invalid {field}: {voice}")));
+            }
+            *target = voice;
+            changed.push(field);
+        }
+        if let Some(style) = self.xai_tts_style {
+            cfg.xai_tts_style = style;
+            changed.push("xai_tts_style");
+        }
+        if changed.is_empty() {
+            return Err(("empty_patch", "no fields to update".to_string()));
+        }
+        Ok(changed)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamFrame {
+    Speak(SpeakRequest),
+}
+
+// WebSocket session: each `speak` frame is queued for TTS and its progress is relayed
+// back as status frames until `done`. Frames sent meanwhile are handled in order after that.
+fn handle_stream(request: Request, tts_tx: &Sender<TtsRequest>, events: &Sender<AppEvent>) {
+    let key = request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Sec-WebSocket-Key"))
+        .map(|h| h.value.as_str().trim().to_string());
+    let Some(key) = key else {
+        let _ = request.respond(error_response(
+            400,
+            "upgrade_required",
+            "websocket upgrade required",
+            None,
+        ));
+        return;
+    };
+    let accept = Header::from_bytes("Sec-WebSocket-Accept", derive_accept_key(key.as_bytes()))
+        .expect("accept header");
+    let stream = request.upgrade("websocket", Response::empty(101).with_header(accept));
+    let mut ws = WebSocket::from_raw_socket(stream, Role::Server, None);
+    let _ = events.send(AppEvent::Info("stream client connected".to_string()));
+
+    while let Ok(message) = ws.read() {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let speak = match serde_json::from_str::<StreamFrame>(&text) {
+            Ok(StreamFrame::Speak(speak)) => speak,
+            Err(e) => {
+                let frame = serde_json::json!({ "type": "error", "code": "invalid_json", "message": e.to_string() });
+                if ws.send(Message::Text(frame.to_string())).is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+        let (progress_tx, progress_rx) = unbounded::<TtsProgress>();
+        let queued = tts_tx.len();
+        if tts_tx
+            .send(TtsRequest {
+                speak,
+                progress: Some(progress_tx),
+            })
+            .is_err()
+        {
+            break;
+        }
+        let frame = serde_json::json!({ "type": "status", "state": "queued", "ahead": queued });
+        if ws.send(Message::Text(frame.to_string())).is_err() {
+            break;
+        }
+        for progress in progress_rx.iter() {
+            let (frame, last) = progress_frame(&progress);
+            if ws.send(Message::Text(frame.to_string())).is_err() {
+                let _ = events.send(AppEvent::Info("stream client disconnected".to_string()));
+                return;
+            }
+            if last {
+                break;
+            }
+        }
+    }
+    let _ = events.send(AppEvent::Info("stream client disconnected".to_string()));
+}
+
+// Quiet periods still get a line now and then, so a gone client is noticed by the
+// failed write instead of holding its thread until the next event.
+const EVENTS_KEEPALIVE: Duration = Duration::from_secs(15);
+
+// `GET /events`: newline-delimited JSON, one line per event, until the client goes away
+// or falls too far behind. Written straight to the socket so each line goes out at once.
+fn handle_events(request: Request, feed: Receiver<String>, events: &Sender<AppEvent>) {
+    let mut writer = request.into_writer();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\n\
+                Cache-Control: no-cache\r\nConnection: close\r\n\r\n";
+    if writer
+        .write_all(head.as_bytes())
+        .and_then(|_| writer.flush())
+        .is_err()
+    {
+        return;
+    }
+    let _ = events.send(AppEvent::Info("events client connected".to_string()));
+    loop {
+        let line = match feed.recv_timeout(EVENTS_KEEPALIVE) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => r#"{"type":"keepalive"}"#.to_string(),
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        if writeln!(writer, "{line}")
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+    let _ = events.send(AppEvent::Info("events client disconnected".to_string()));
+}
+
+fn progress_frame(progress: &TtsProgress) -> (serde_json::Value, bool) {
+    match progress {
+        TtsProgress::Started { chunks } => (
+            serde_json::json!({ "type": "status", "state": "started", "chunks": chunks }),
+            false,
+        ),
+        TtsProgress::Chunk { index, total } => (
+            serde_json::json!({ "type": "status", "state": "chunk", "index": index, "total": total }),
+            false,
+        ),
+        TtsProgress::ChunkFailed { index, error } => (
+            serde_json::json!({ "type": "status", "state": "chunk_failed", "index": index, "error": error }),
+            false,
+        ),
+        TtsProgress::Done { played, total } => (
+            serde_json::json!({ "type": "done", "played": played, "total": total }),
+            true,
+        ),
+        TtsProgress::Rejected(error) => (
+            serde_json::json!({ "type": "error", "code": "tts_rejected", "message": error }),
+            true,
+        ),
+        TtsProgress::Failed(error) => (
+            serde_json::json!({ "type": "error", "code": "tts_failed", "message": error }),
+            true,
+        ),
+    }
+}
+
+// An empty `server_auth_token` leaves protected routes open, matching the pre-token behavior.
+// The token may also come as `?token=` for clients that can't set headers (browser WebSockets).
+fn authorized(request: &Request, query: &str, config: &Arc<Mutex<AppConfig>>) -> bool {
+    let token = config::lock(config).server_auth_token.clone();
+    if token.is_empty() || query_param(query, "token") == Some(token.as_str()) {
+        return true;
+    }
+    request.headers().iter().any(|h| {
+        h.field.equiv("Authorization")
+            && h.value
+                .as_str()
+                .strip_prefix("Bearer ")
+                .is_some_and(|v| v.trim() == token)
     })
 }
 
+// Every error body has the same shape: `{"error":{"code","message","detail"}}`.
+// Codes are stable for clients to match on; messages and details are for humans.
+fn error_body(code: &str, message: &str, detail: Option<String>) -> serde_json::Value {
+    serde_json::json!({ "code": code, "message": message, "detail": detail })
+}
+
+fn error_response(
+    status: u16,
+    code: &str,
+    message: &str,
+    detail: Option<String>,
+) -> Response<std::io::Cursor<Vec<u8>>> {
+    let body = serde_json::json!({ "error": error_body(code, message, detail) });
+    json_response(&body.to_string(), status)
+}
+
+fn unauthorized() -> Response<std::io::Cursor<Vec<u8>>> {
+    error_response(401, "unauthorized", "missing or wrong auth token", None)
+}
+
+// Methods each known path accepts, for 405 responses and their `Allow` header.
+fn allowed_methods(path: &str) -> Option<&'static str> {
+    match path {
+        "/health" | "/status" | "/stream" | "/events" => Some("GET"),
+        "/config" => Some("GET, POST"),
+        "/speak" | "/transcribe" | "/listen" | "/stop-listen" => Some("POST"),
+        _ => None,
+    }
+}
+
 fn json_response(body: &str, status: u16) -> Response<std::io::Cursor<Vec<u8>>> {
     let content_type =
         Header::from_bytes("Content-Type", "application/json").expect("static header");
@@ -1,53 +1,269 @@
 use std::{
-    io::Cursor,
+    collections::VecDeque,
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
 use crossbeam_channel::{Receiver, Sender};
 use reqwest::blocking::{Client, multipart};
 
 use crate::{
-    app::AppEvent,
-    config::{AppConfig, Provider},
-    inject::TextInjector,
+    app::{AppEvent, Status},
+    config::{self, AppConfig, Provider, SttBackend, SttCoalesce, UploadFormat},
+    dsp, flac,
+    http::{self, TimedClient},
+    inject::{self, TextInjector},
+    logging, supervisor, transcript,
+    tts::{SpeakRequest, TtsRequest},
 };
 
+// Read-back is for a quick check by ear; longer dictation would tie up the speaker.
+const READ_BACK_MAX_CHARS: usize = 300;
+
 pub fn spawn_stt_worker(
     config: Arc<Mutex<AppConfig>>,
     events: Sender<AppEvent>,
-    stt_rx: Receiver<Vec<i16>>,
+    stt_rx: Receiver<SttJob>,
     injector: Arc<TextInjector>,
     sample_rate: u32,
+    tts_tx: Sender<TtsRequest>,
+    status: Arc<Mutex<Status>>,
 ) {
-    thread::spawn(move || {
-        let http = Client::new();
-        while let Ok(samples) = stt_rx.recv() {
+    supervisor::spawn("stt", events.clone(), config.clone(), move || {
+        // Clears a busy flag left behind if the previous run crashed mid-job.
+        let _ = events.send(AppEvent::SttBusy(false));
+        let mut http = TimedClient::new(config::lock(&config).http_timeout_secs);
+        let mut backlog = VecDeque::new();
+        let mut replacements = transcript::Replacements::default();
+        loop {
+            let job = match backlog.pop_front() {
+                Some(job) => job,
+                None => match stt_rx.recv() {
+                    Ok(job) => job,
+                    Err(_) => break,
+                },
+            };
             let _ = events.send(AppEvent::SttBusy(true));
+            let mut current = config::lock(&config).clone();
+            backlog.extend(stt_rx.try_iter());
+            let (job, folded) = coalesce(job, &mut backlog, current.stt_coalesce, sample_rate);
+            if folded > 0 {
+                let what = match current.stt_coalesce {
+                    SttCoalesce::Latest => "dropped older",
+                    _ => "merged",
+                };
+                let _ = events.send(AppEvent::Info(format!(
+                    "stt: {what} {folded} queued capture(s)"
+                )));
+            }
+            job.overrides.apply(&mut current);
+            if job.test {
+                current.inject_enabled = false;
+            }
+            let mut samples = job.samples;
+            if current.noise_gate_enabled {
+                dsp::apply_noise_gate(&mut samples, sample_rate, current.noise_gate_threshold);
+            }
             let seconds_raw = samples.len() as f32 / sample_rate as f32;
             let _ = events.send(AppEvent::Info(format!(
                 "stt audio seconds raw={seconds_raw:.2}"
             )));
-            let res = transcribe_with_provider(&http, &config, &samples, sample_rate);
+            let report_test = |provider: &'static str, outcome: Result<String, String>| {
+                if let Some(reply) = &job.reply {
+                    let _ = reply.send((provider, outcome));
+                } else if job.test {
+                    let _ = events.send(AppEvent::SttTestResult {
+                        provider,
+                        seconds: seconds_raw,
+                        outcome,
+                    });
+                }
+            };
+            let recordings_dir = current
+                .save_recordings_dir
+                .clone()
+                .filter(|dir| !dir.trim().is_empty());
+            let recording = recordings_dir.and_then(|dir| {
+                match save_recording(Path::new(dir.trim()), &samples, sample_rate) {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        let _ =
+                            events.send(AppEvent::Warning(format!("saving recording failed: {e}")));
+                        None
+                    }
+                }
+            });
+            let client = http.get(current.http_timeout_secs);
+            let started = Instant::now();
+            let res = transcribe_for_dictation(client, &current, &samples, sample_rate, &events);
+            let _ = events.send(AppEvent::Metric {
+                stage: "stt",
+                ms: started.elapsed().as_millis() as u64,
+            });
             match res {
-                Ok((provider, text)) if !text.is_empty() => {
-                    let _ = events.send(AppEvent::Info(format!(
-                        "stt provider used: {}",
-                        provider_name(&provider)
-                    )));
+                Ok(Transcript {
+                    provider,
+                    text,
+                    confidence,
+                }) if !text.is_empty() => {
+                    let _ = events.send(AppEvent::Info(format!("stt provider used: {provider}")));
+                    if let Some(score) = confidence
+                        && score < current.stt_min_confidence
+                    {
+                        let _ = events.send(AppEvent::Info(format!(
+                            "transcript suppressed (confidence {score:.2} < {:.2}): {text}",
+                            current.stt_min_confidence
+                        )));
+                        report_test(
+                            provider,
+                            Err(format!("suppressed by min confidence ({score:.2}): {text}")),
+                        );
+                        let _ = events.send(AppEvent::SttBusy(false));
+                        continue;
+                    }
+                    let text = match transcript::strip_prefix(&text, &current.strip_prefixes) {
+                        Some((rest, prefix)) => {
+                            let _ = events.send(AppEvent::Info(format!(
+                                "stripped transcript prefix '{prefix}'"
+                            )));
+                            rest
+                        }
+                        None => text,
+                    };
+                    if text.is_empty() {
+                        let _ = events.send(AppEvent::Info(
+                            "transcript was only a stripped prefix; nothing to paste".to_string(),
+                        ));
+                        report_test(provider, Ok(String::new()));
+                        let _ = events.send(AppEvent::SttBusy(false));
+                        continue;
+                    }
+                    // Before replacements, so user rules keep the final say on casing.
+                    let text = if current.auto_capitalize {
+                        transcript::auto_capitalize(&text, current.auto_period)
+                    } else {
+                        text
+                    };
+                    for error in replacements.update(&current.replacements) {
+                        let _ = events.send(AppEvent::Warning(error));
+                    }
+                    let (text, fired) = replacements.apply(&text);
+                    if !fired.is_empty() {
+                        let _ = events.send(AppEvent::Info(format!(
+                            "replacement rules applied: {}",
+                            fired.join(", ")
+                        )));
+                    }
+                    let text = match current
+                        .inject_command
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|command| !command.is_empty())
+                    {
+                        Some(command) => {
+                            let timeout = Duration::from_millis(current.inject_command_timeout_ms);
+                            match inject::pipe_through_command(command, &text, timeout) {
+                                Ok(filtered) => filtered,
+                                Err(e) => {
+                                    let _ = events.send(AppEvent::Warning(format!(
+                                        "inject command failed ({e}); using the raw transcript"
+                                    )));
+                                    text
+                                }
+                            }
+                        }
+                        None => text,
+                    };
+                    if let Some(path) = &recording
+                        && let Err(e) = fs::write(path.with_extension("txt"), &text)
+                    {
+                        let _ = events.send(AppEvent::Warning(format!(
+                            "saving transcript sidecar failed: {e}"
+                        )));
+                    }
                     let _ = events.send(AppEvent::LastTranscript(text.clone()));
-                    if let Err(e) = injector.inject_text(&text) {
-                        let _ = events.send(AppEvent::Error(format!("inject failed: {e}")));
+                    report_test(provider, Ok(text.clone()));
+                    let payload = format!("{text}{}", current.inject_suffix);
+                    if current.inject_enabled
+                        && !inject::wait_for_stable_focus(
+                            Duration::from_millis(current.inject_focus_stable_ms),
+                            Duration::from_millis(current.inject_focus_timeout_ms),
+                        )
+                    {
+                        let _ = events.send(AppEvent::Warning(format!(
+                            "foreground window still changing after {} ms; pasting anyway",
+                            current.inject_focus_timeout_ms
+                        )));
+                    }
+                    if !current.inject_enabled {
+                        let _ = events.send(AppEvent::Info(
+                            "injection off (dry run); transcript not pasted".to_string(),
+                        ));
+                    } else if let Err(reason) =
+                        inject::check_window_allowlist(&current.inject_allowlist)
+                    {
+                        let _ = events.send(AppEvent::Info(format!(
+                            "injection skipped ({reason}); transcript copied to clipboard"
+                        )));
+                        if let Err(e) = injector.copy_text(&payload) {
+                            let _ = events.send(AppEvent::Error(format!("copy failed: {e}")));
+                        }
+                    } else {
+                        let settle = Duration::from_millis(current.inject_settle_ms);
+                        match injector.inject_text(
+                            &payload,
+                            &current.paste_shortcut,
+                            settle,
+                            current.inject_verify,
+                            current.inject_placement,
+                        ) {
+                            Ok(true) => {
+                                if current.inject_submit
+                                    && let Err(e) = injector.press_enter(settle)
+                                {
+                                    let _ =
+                                        events.send(AppEvent::Error(format!("submit failed: {e}")));
+                                }
+                                if current.speak_transcript_back {
+                                    read_back(&text, &tts_tx, &status, &events);
+                                }
+                            }
+                            Ok(false) => {
+                                // Never submit what may be a stale clipboard.
+                                let skipped = if current.inject_submit {
+                                    "; not pressing Enter"
+                                } else {
+                                    ""
+                                };
+                                let _ = events.send(AppEvent::Warning(format!(
+                                    "clipboard did not update before paste (after retry); \
+                                     the paste may be empty, try a longer settle delay{skipped}"
+                                )));
+                            }
+                            Err(e) => {
+                                let _ = events.send(AppEvent::Error(format!("inject failed: {e}")));
+                            }
+                        }
                     }
                 }
-                Ok((provider, _)) => {
+                Ok(Transcript { provider, .. }) => {
                     let _ = events.send(AppEvent::Info(format!(
-                        "stt produced empty transcript (provider: {})",
-                        provider_name(&provider)
+                        "stt produced empty transcript (provider: {provider})"
                     )));
+                    report_test(provider, Ok(String::new()));
                 }
                 Err(e) => {
-                    let _ = events.send(AppEvent::Error(format!("stt failed: {e}")));
+                    let error = http::describe(&e, current.http_timeout_secs);
+                    let _ = events.send(AppEvent::Error(format!("stt failed: {error}")));
+                    let provider = if current.stt_backend == SttBackend::Local {
+                        "local whisper"
+                    } else {
+                        provider_name(&current.stt_provider)
+                    };
+                    report_test(provider, Err(error));
                 }
             }
             let _ = events.send(AppEvent::SttBusy(false));
@@ -55,60 +271,478 @@ pub fn spawn_stt_worker(
     });
 }
 
-fn transcribe_with_provider(
+// Silence between merged captures so the last word of one doesn't run into the next.
+const MERGE_GAP_MS: u64 = 300;
+
+// Folds the dictation captures queued right behind `job` into it: `Merge` joins them
+// (same binding overrides only), `Latest` keeps the newest. Test jobs are never folded
+// and stop the scan so everything stays in order. Returns how many were folded.
+fn coalesce(
+    mut job: SttJob,
+    backlog: &mut VecDeque<SttJob>,
+    mode: SttCoalesce,
+    sample_rate: u32,
+) -> (SttJob, usize) {
+    if mode == SttCoalesce::Fifo || job.test {
+        return (job, 0);
+    }
+    let gap = (sample_rate as u64 * MERGE_GAP_MS / 1000) as usize;
+    let mut folded = 0;
+    while let Some(next) = backlog.front() {
+        if next.test || (mode == SttCoalesce::Merge && next.overrides != job.overrides) {
+            break;
+        }
+        let next = backlog.pop_front().expect("front checked above");
+        match mode {
+            SttCoalesce::Merge => {
+                job.samples.extend(std::iter::repeat_n(0, gap));
+                job.samples.extend(next.samples);
+            }
+            _ => job = next,
+        }
+        folded += 1;
+    }
+    (job, folded)
+}
+
+// One capture's worth of audio, plus any provider/model chosen by the hotkey binding
+// that recorded it.
+pub struct SttJob {
+    pub samples: Vec<i16>,
+    pub overrides: SttOverrides,
+    // Set by the Tools "Test STT" button: never paste, report back via SttTestResult.
+    pub test: bool,
+    // Diagnostics want the outcome themselves instead of in the Test STT box.
+    pub reply: Option<Sender<SttOutcome>>,
+}
+
+pub type SttOutcome = (&'static str, Result<String, String>);
+
+impl SttJob {
+    pub fn new(samples: Vec<i16>) -> Self {
+        Self {
+            samples,
+            overrides: SttOverrides::default(),
+            test: false,
+            reply: None,
+        }
+    }
+
+    pub fn test(samples: Vec<i16>) -> Self {
+        Self {
+            test: true,
+            ..Self::new(samples)
+        }
+    }
+
+    pub fn diagnostic(samples: Vec<i16>, reply: Sender<SttOutcome>) -> Self {
+        Self {
+            reply: Some(reply),
+            ..Self::test(samples)
+        }
+    }
+}
+
+// Speaks a pasted transcript through the normal TTS queue. Skipped while a new capture
+// is already recording, so the mic never hears its own read-back; captures that start
+// during playback are covered by `duck_tts_on_listen`.
+fn read_back(
+    text: &str,
+    tts_tx: &Sender<TtsRequest>,
+    status: &Mutex<Status>,
+    events: &Sender<AppEvent>,
+) {
+    let length = text.chars().count();
+    let skip = if length > READ_BACK_MAX_CHARS {
+        Some(format!("{length} chars is over {READ_BACK_MAX_CHARS}"))
+    } else if status.lock().is_ok_and(|status| status.listening) {
+        Some("a capture is recording".to_string())
+    } else {
+        None
+    };
+    if let Some(reason) = skip {
+        let _ = events.send(AppEvent::Info(format!(
+            "transcript read-back skipped ({reason})"
+        )));
+        return;
+    }
+    let _ = tts_tx.send(TtsRequest {
+        speak: SpeakRequest {
+            message: text.to_string(),
+            persona: None,
+            voice: None,
+            provider: None,
+            show_text: Some(false),
+            style: None,
+        },
+        progress: None,
+    });
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SttOverrides {
+    pub provider: Option<Provider>,
+    pub model: Option<String>,
+    pub language: Option<String>,
+}
+
+impl SttOverrides {
+    fn apply(&self, cfg: &mut AppConfig) {
+        if let Some(provider) = self.provider {
+            // Naming a cloud provider on a binding means "use it", even with local whisper on.
+            cfg.stt_provider = provider;
+            cfg.stt_backend = SttBackend::Hosted;
+        }
+        if let Some(model) = &self.model {
+            cfg.set_stt_model_for(cfg.stt_provider, model.clone());
+        }
+        if let Some(language) = &self.language {
+            cfg.stt_language = language.clone();
+            cfg.set_stt_language_for(cfg.stt_provider, language.clone());
+        }
+    }
+}
+
+pub struct Transcript {
+    pub provider: &'static str,
+    pub text: String,
+    // 0..1, only when the provider returned segment scores (verbose_json).
+    pub confidence: Option<f32>,
+}
+
+pub fn transcribe_with_provider(
     client: &Client,
-    cfg: &Arc<Mutex<AppConfig>>,
+    current: &AppConfig,
     samples: &[i16],
     sample_rate: u32,
-) -> anyhow::Result<(Provider, String)> {
-    let current = cfg.lock().expect("config lock").clone();
+    events: &Sender<AppEvent>,
+) -> anyhow::Result<Transcript> {
+    if current.mock_mode {
+        let seconds = samples.len() as f32 / sample_rate.max(1) as f32;
+        return Ok(Transcript {
+            provider: "mock",
+            text: format!("Mock transcript of a {seconds:.1} second clip."),
+            confidence: None,
+        });
+    }
+    if current.stt_backend == SttBackend::Local {
+        let text = transcribe_local(current, samples, sample_rate)?;
+        return Ok(Transcript {
+            provider: "local whisper",
+            text,
+            confidence: None,
+        });
+    }
     let provider = current.stt_provider;
+    if provider == Provider::ElevenLabs {
+        return Err(anyhow::anyhow!(
+            "elevenlabs is TTS-only; choose another STT provider"
+        ));
+    }
     let key = current
         .stt_key(&provider)
         .ok_or_else(|| anyhow::anyhow!("missing API key for {}", provider_name(&provider)))?;
     let model = current.stt_model_for(&provider);
-    let text = transcribe_once(
-        client,
-        &provider,
-        &key,
-        &model,
-        &current.stt_language,
-        samples,
-        sample_rate,
-    )?;
-    Ok((provider, text))
+    let base_url = current.stt_base_url(&provider);
+    let language = current.stt_language_for(&provider);
+    let request = TranscribeRequest {
+        provider: &provider,
+        base_url: &base_url,
+        api_key: &key,
+        model: &model,
+        language: &language,
+        prompt: &current.stt_prompt,
+        verbose: current.stt_min_confidence > 0.0 && supports_verbose_json(&provider, &model),
+        format: current.stt_upload_format,
+    };
+    let (text, confidence) = transcribe_once(client, &request, samples, sample_rate, events)?;
+    Ok(Transcript {
+        provider: provider_name(&provider),
+        text,
+        confidence,
+    })
 }
 
-fn transcribe_once(
+// Dictation entry point: streams partial text into the UI when `stt_streaming` is on
+// and the model supports it, and falls back to the one-shot upload on any error.
+fn transcribe_for_dictation(
     client: &Client,
-    provider: &Provider,
-    api_key: &str,
-    model: &str,
-    language: &str,
+    current: &AppConfig,
     samples: &[i16],
     sample_rate: u32,
+    events: &Sender<AppEvent>,
+) -> anyhow::Result<Transcript> {
+    let provider = current.stt_provider;
+    let model = current.stt_model_for(&provider);
+    if current.mock_mode
+        || !current.stt_streaming
+        || current.stt_backend == SttBackend::Local
+        || !supports_streaming(&provider, &model)
+    {
+        return transcribe_with_provider(client, current, samples, sample_rate, events);
+    }
+    let key = current
+        .stt_key(&provider)
+        .ok_or_else(|| anyhow::anyhow!("missing API key for {}", provider_name(&provider)))?;
+    let base_url = current.stt_base_url(&provider);
+    let language = current.stt_language_for(&provider);
+    let request = TranscribeRequest {
+        provider: &provider,
+        base_url: &base_url,
+        api_key: &key,
+        model: &model,
+        language: &language,
+        prompt: &current.stt_prompt,
+        verbose: false,
+        format: current.stt_upload_format,
+    };
+    let streamed = transcribe_streaming(client, &request, samples, sample_rate, |partial| {
+        let _ = events.send(AppEvent::LastTranscript(partial.to_string()));
+    });
+    match streamed {
+        Ok(text) => Ok(Transcript {
+            provider: provider_name(&provider),
+            text,
+            confidence: None,
+        }),
+        // A stalled provider would just stall again; don't double the wait.
+        Err(e) if http::is_timeout(&e) => Err(e),
+        Err(e) => {
+            let _ = events.send(AppEvent::Warning(format!(
+                "streaming stt failed ({e}); retrying as one-shot"
+            )));
+            transcribe_with_provider(client, current, samples, sample_rate, events)
+        }
+    }
+}
+
+// OpenAI's gpt-4o transcribe models can answer with server-sent events; whisper-1 can't.
+fn supports_streaming(provider: &Provider, model: &str) -> bool {
+    *provider == Provider::OpenAi && model.contains("transcribe")
+}
+
+fn transcribe_streaming(
+    client: &Client,
+    request: &TranscribeRequest,
+    samples: &[i16],
+    sample_rate: u32,
+    on_partial: impl FnMut(&str),
+) -> anyhow::Result<String> {
+    let upload = encode_upload(request.format, samples, sample_rate)?;
+    let form = transcription_form(request, upload)?.text("stream", "true");
+    let response = client
+        .post(transcriptions_url(request))
+        .bearer_auth(request.api_key)
+        .multipart(form)
+        .send()?;
+    if !response.status().is_success() {
+        return Err(http::provider_error(*request.provider, response));
+    }
+    read_transcript_events(BufReader::new(response), on_partial)
+}
+
+// Accumulates `transcript.text.delta` events and returns the text from
+// `transcript.text.done`; a stream that ends without it is an error.
+fn read_transcript_events(
+    reader: impl BufRead,
+    mut on_partial: impl FnMut(&str),
 ) -> anyhow::Result<String> {
-    let wav = pcm_to_wav_bytes(samples, sample_rate)?;
-    let url = format!(
+    let mut partial = String::new();
+    for line in reader.lines() {
+        let line = line?;
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            continue;
+        };
+        if data.is_empty() || data == "[DONE]" {
+            continue;
+        }
+        let event: serde_json::Value = serde_json::from_str(data)?;
+        let field = |name: &str| event.get(name).and_then(|v| v.as_str()).unwrap_or_default();
+        match field("type") {
+            "transcript.text.delta" => {
+                partial.push_str(field("delta"));
+                on_partial(partial.trim());
+            }
+            "transcript.text.done" => return Ok(field("text").trim().to_string()),
+            "error" => {
+                let message = event
+                    .pointer("/error/message")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown error");
+                return Err(anyhow::anyhow!("stream error: {message}"));
+            }
+            _ => {}
+        }
+    }
+    Err(anyhow::anyhow!("stream ended before the final transcript"))
+}
+
+// Writes the capture as `<timestamp>.wav`; the transcript sidecar is added once STT returns.
+fn save_recording(dir: &Path, samples: &[i16], sample_rate: u32) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "{}.wav",
+        logging::file_timestamp(SystemTime::now())
+    ));
+    fs::write(&path, dsp::pcm_to_wav_bytes(samples, sample_rate)?)?;
+    Ok(path)
+}
+
+// Runs whisper.cpp in-process against a local model; no network or API key involved.
+// The model is loaded on first use and kept until the configured path changes.
+#[cfg(feature = "local-whisper")]
+fn transcribe_local(cfg: &AppConfig, samples: &[i16], sample_rate: u32) -> anyhow::Result<String> {
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    const WHISPER_SAMPLE_RATE: u32 = 16_000;
+    static MODEL: Mutex<Option<(String, WhisperContext)>> = Mutex::new(None);
+
+    let model = cfg.local_whisper_model.trim();
+    if model.is_empty() {
+        return Err(anyhow::anyhow!("local whisper model path is not set"));
+    }
+    let mut loaded = MODEL
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if loaded.as_ref().is_none_or(|(path, _)| path != model) {
+        // Drop the old model first so two don't sit in memory at once.
+        *loaded = None;
+        whisper_rs::install_logging_hooks();
+        let context = WhisperContext::new_with_params(model, WhisperContextParameters::default())
+            .map_err(|e| anyhow::anyhow!("failed to load whisper model {model}: {e}"))?;
+        *loaded = Some((model.to_string(), context));
+    }
+    let (_, context) = loaded.as_ref().expect("model loaded above");
+    let mut state = context.create_state()?;
+
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_language(Some(language_hint(&cfg.stt_language).unwrap_or("auto")));
+    if !cfg.stt_prompt.trim().is_empty() {
+        params.set_initial_prompt(cfg.stt_prompt.trim());
+    }
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+
+    let audio: Vec<f32> = dsp::resample_linear(samples, sample_rate, WHISPER_SAMPLE_RATE)
+        .iter()
+        .map(|&s| f32::from(s) / 32768.0)
+        .collect();
+    state.full(params, &audio)?;
+    let text = state
+        .as_iter()
+        .filter_map(|segment| segment.to_str_lossy().ok().map(|s| s.trim().to_string()))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Ok(text)
+}
+
+#[cfg(not(feature = "local-whisper"))]
+fn transcribe_local(_: &AppConfig, _: &[i16], _: u32) -> anyhow::Result<String> {
+    Err(anyhow::anyhow!(
+        "this build has no local whisper; rebuild with --features local-whisper"
+    ))
+}
+
+// Per-request file cap. OpenAI documents 25 MB, as does Groq's free tier; xAI doesn't
+// publish one, so it gets the same.
+const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+
+struct TranscribeRequest<'a> {
+    provider: &'a Provider,
+    base_url: &'a str,
+    api_key: &'a str,
+    model: &'a str,
+    language: &'a str,
+    prompt: &'a str,
+    verbose: bool,
+    format: UploadFormat,
+}
+
+fn transcriptions_url(request: &TranscribeRequest) -> String {
+    format!(
         "{}/audio/transcriptions",
-        AppConfig::stt_base_url(provider).trim_end_matches('/')
-    );
-    let part = multipart::Part::bytes(wav)
-        .file_name("speech.wav")
-        .mime_str("audio/wav")?;
+        request.base_url.trim_end_matches('/')
+    )
+}
+
+fn transcription_form(
+    request: &TranscribeRequest,
+    upload: Upload,
+) -> anyhow::Result<multipart::Form> {
+    let part = multipart::Part::bytes(upload.bytes)
+        .file_name(upload.file_name)
+        .mime_str(upload.mime)?;
     let mut form = multipart::Form::new()
-        .text("model", model.to_string())
+        .text("model", request.model.to_string())
         .part("file", part);
-    if !language.trim().is_empty() {
-        form = form.text("language", language.trim().to_string());
+    if let Some(language) = language_hint(request.language) {
+        form = form.text("language", language.to_string());
+    }
+    if request.verbose {
+        form = form.text("response_format", "verbose_json");
+    }
+    if supports_prompt(request.provider) && !request.prompt.trim().is_empty() {
+        form = form.text("prompt", request.prompt.trim().to_string());
+    }
+    Ok(form)
+}
+
+struct Upload {
+    bytes: Vec<u8>,
+    file_name: &'static str,
+    mime: &'static str,
+}
+
+// Anything the FLAC encoder can't represent still goes up as WAV rather than failing the capture.
+fn encode_upload(
+    format: UploadFormat,
+    samples: &[i16],
+    sample_rate: u32,
+) -> anyhow::Result<Upload> {
+    if format == UploadFormat::Flac
+        && let Ok(bytes) = flac::encode_mono_i16(samples, sample_rate)
+    {
+        return Ok(Upload {
+            bytes,
+            file_name: "speech.flac",
+            mime: "audio/flac",
+        });
+    }
+    Ok(Upload {
+        bytes: dsp::pcm_to_wav_bytes(samples, sample_rate)?,
+        file_name: "speech.wav",
+        mime: "audio/wav",
+    })
+}
+
+fn transcribe_once(
+    client: &Client,
+    request: &TranscribeRequest,
+    samples: &[i16],
+    sample_rate: u32,
+    events: &Sender<AppEvent>,
+) -> anyhow::Result<(String, Option<f32>)> {
+    let upload = encode_upload(request.format, samples, sample_rate)?;
+    let size = upload.bytes.len();
+    if size > MAX_UPLOAD_BYTES {
+        return transcribe_split(client, request, samples, sample_rate, size, events);
     }
+    let _ = events.send(AppEvent::Info(format!(
+        "stt upload {:.1} KB ({})",
+        size as f32 / 1024.0,
+        upload.file_name
+    )));
+    let form = transcription_form(request, upload)?;
     let response = client
-        .post(url)
-        .bearer_auth(api_key)
+        .post(transcriptions_url(request))
+        .bearer_auth(request.api_key)
         .multipart(form)
         .send()?;
     if !response.status().is_success() {
-        return Err(anyhow::anyhow!("status {}", response.status()));
+        return Err(http::provider_error(*request.provider, response));
     }
     let body: serde_json::Value = response.json()?;
     let text = body
@@ -117,25 +751,82 @@ fn transcribe_once(
         .unwrap_or_default()
         .trim()
         .to_string();
-    Ok(text)
+    Ok((text, segment_confidence(&body)))
 }
 
-fn pcm_to_wav_bytes(samples: &[i16], sample_rate: u32) -> anyhow::Result<Vec<u8>> {
-    let mut cursor = Cursor::new(Vec::new());
-    let mut writer = hound::WavWriter::new(
-        &mut cursor,
-        hound::WavSpec {
-            channels: 1,
-            sample_rate,
-            bits_per_sample: 16,
-            sample_format: hound::SampleFormat::Int,
-        },
-    )?;
-    for &sample in samples {
-        writer.write_sample(sample)?;
+// Over the provider's size cap: transcribe the capture in pieces, in order, and join the text.
+// Confidence is the length-weighted mean of whichever pieces reported one.
+fn transcribe_split(
+    client: &Client,
+    request: &TranscribeRequest,
+    samples: &[i16],
+    sample_rate: u32,
+    size: usize,
+    events: &Sender<AppEvent>,
+) -> anyhow::Result<(String, Option<f32>)> {
+    let pieces = size.div_ceil(MAX_UPLOAD_BYTES).max(2);
+    let _ = events.send(AppEvent::Info(format!(
+        "stt upload {:.1} MB is over the {} MB limit; sending it in {pieces} parts",
+        size as f32 / (1024.0 * 1024.0),
+        MAX_UPLOAD_BYTES / (1024 * 1024)
+    )));
+    let mut texts = Vec::new();
+    let mut weighted = 0.0f32;
+    let mut scored = 0usize;
+    for part in dsp::split_at_quiet(samples, pieces, sample_rate) {
+        let (text, confidence) = transcribe_once(client, request, part, sample_rate, events)?;
+        if !text.is_empty() {
+            texts.push(text);
+        }
+        if let Some(score) = confidence {
+            weighted += score * part.len() as f32;
+            scored += part.len();
+        }
     }
-    writer.finalize()?;
-    Ok(cursor.into_inner())
+    let confidence = (scored > 0).then(|| weighted / scored as f32);
+    Ok((texts.join(" "), confidence))
+}
+
+// Duration-weighted mean of exp(avg_logprob) * (1 - no_speech_prob) over verbose_json segments.
+fn segment_confidence(body: &serde_json::Value) -> Option<f32> {
+    let segments = body.get("segments")?.as_array()?;
+    let mut weighted = 0.0f64;
+    let mut total = 0.0f64;
+    for segment in segments {
+        let field = |name: &str| segment.get(name).and_then(|v| v.as_f64());
+        let (Some(avg_logprob), Some(no_speech)) = (field("avg_logprob"), field("no_speech_prob"))
+        else {
+            continue;
+        };
+        let score = avg_logprob.exp().clamp(0.0, 1.0) * (1.0 - no_speech.clamp(0.0, 1.0));
+        let duration = match (field("start"), field("end")) {
+            (Some(start), Some(end)) if end > start => end - start,
+            _ => 1.0,
+        };
+        weighted += score * duration;
+        total += duration;
+    }
+    (total > 0.0).then(|| (weighted / total) as f32)
+}
+
+// Only Whisper models return segment scores; gpt-4o transcribe models accept json/text only.
+fn supports_verbose_json(provider: &Provider, model: &str) -> bool {
+    matches!(provider, Provider::OpenAi | Provider::Groq) && model.contains("whisper")
+}
+
+// Empty or "auto" omits the language field so the provider auto-detects.
+fn language_hint(language: &str) -> Option<&str> {
+    let language = language.trim();
+    if language.is_empty() || language.eq_ignore_ascii_case("auto") {
+        None
+    } else {
+        Some(language)
+    }
+}
+
+// OpenAI and Groq accept a vocabulary-biasing prompt; other backends just skip it.
+fn supports_prompt(provider: &Provider) -> bool {
+    matches!(provider, Provider::OpenAi | Provider::Groq)
 }
 
 fn provider_name(provider: &Provider) -> &'static str {
@@ -143,5 +834,6 @@ fn provider_name(provider: &Provider) -> &'static str {
         Provider::Xai => "xai",
         Provider::OpenAi => "openai",
         Provider::Groq => "groq",
+        Provider::ElevenLabs => "elevenlabs",
     }
 }
@@ -0,0 +1,68 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, Mutex},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::Sender;
+
+use crate::{app::AppEvent, config::AppConfig};
+
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+// More crashes than this inside the window means the worker is stuck in a crash loop.
+const MAX_CRASHES: usize = 5;
+const CRASH_WINDOW: Duration = Duration::from_secs(60);
+
+// Runs `body` on its own thread and runs it again if it panics. A normal return ends
+// the thread. The body is called once per (re)start, so it must clone whatever it
+// consumes. The shared config is un-poisoned before a restart: `config::lock` would
+// recover it anyway, but would also log a poisoned-lock warning for a crash that has
+// already been reported here.
+pub fn spawn<F>(
+    name: &'static str,
+    events: Sender<AppEvent>,
+    config: Arc<Mutex<AppConfig>>,
+    body: F,
+) -> JoinHandle<()>
+where
+    F: Fn() + Send + 'static,
+{
+    thread::Builder::new()
+        .name(format!("{name} worker"))
+        .spawn(move || {
+            let mut crashes: Vec<Instant> = Vec::new();
+            loop {
+                let Err(payload) = panic::catch_unwind(AssertUnwindSafe(&body)) else {
+                    return;
+                };
+                config.clear_poison();
+                crashes.retain(|at| at.elapsed() < CRASH_WINDOW);
+                crashes.push(Instant::now());
+                let reason = panic_message(payload.as_ref());
+                if crashes.len() > MAX_CRASHES {
+                    let _ = events.send(AppEvent::Error(format!(
+                        "{name} worker crashed {} times in a minute ({reason}); not restarting",
+                        crashes.len()
+                    )));
+                    return;
+                }
+                let _ = events.send(AppEvent::Error(format!(
+                    "{name} worker crashed ({reason}); restarting"
+                )));
+                thread::sleep(RESTART_DELAY);
+            }
+        })
+        .expect("spawn worker thread")
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
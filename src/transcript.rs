@@ -0,0 +1,220 @@
+use std::borrow::Cow;
+
+use regex::Regex;
+
+use crate::config::ReplacementRule;
+
+// The configured rules with their regexes compiled; rebuilt only when the rules change,
+// so patterns aren't recompiled for every transcript.
+#[derive(Default)]
+pub struct Replacements {
+    rules: Vec<ReplacementRule>,
+    patterns: Vec<Option<Regex>>,
+}
+
+impl Replacements {
+    // Returns a message for each regex rule that doesn't compile; those rules are skipped.
+    pub fn update(&mut self, rules: &[ReplacementRule]) -> Vec<String> {
+        if self.rules == rules {
+            return Vec::new();
+        }
+        let mut errors = Vec::new();
+        self.patterns = rules
+            .iter()
+            .map(|rule| {
+                if !rule.regex || rule.find.is_empty() {
+                    return None;
+                }
+                compile_rule(&rule.find)
+                    .map_err(|e| errors.push(format!("replacement rule skipped: {e}")))
+                    .ok()
+            })
+            .collect();
+        self.rules = rules.to_vec();
+        errors
+    }
+
+    pub fn apply(&self, text: &str) -> (String, Vec<String>) {
+        let mut out = text.to_string();
+        let mut fired = Vec::new();
+        for (rule, pattern) in self.rules.iter().zip(&self.patterns) {
+            let replaced = match pattern {
+                Some(pattern) => match pattern.replace_all(&out, rule.replace.as_str()) {
+                    Cow::Owned(replaced) => Some(replaced),
+                    Cow::Borrowed(_) => None,
+                },
+                None if rule.regex => None,
+                None => replace_literal(&out, rule),
+            };
+            if let Some(replaced) = replaced {
+                out = replaced;
+                fired.push(format!("{} -> {}", rule.find, rule.replace));
+            }
+        }
+        (out, fired)
+    }
+}
+
+// regex's syntax errors span several lines with a caret under the problem; the last
+// line is the part worth logging.
+pub fn compile_rule(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| {
+        let message = e.to_string();
+        let reason = message.lines().last().unwrap_or_default().trim();
+        format!("/{pattern}/: {}", reason.trim_start_matches("error: "))
+    })
+}
+
+fn replace_literal(text: &str, rule: &ReplacementRule) -> Option<String> {
+    let needle: Vec<char> = rule.find.chars().collect();
+    if needle.is_empty() {
+        return None;
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut cursor = 0usize;
+    let mut hit = false;
+    while let Some((start, end)) = find_case_insensitive(text, &needle, cursor) {
+        result.push_str(&text[cursor..start]);
+        result.push_str(&rule.replace);
+        cursor = end;
+        hit = true;
+    }
+    if !hit {
+        return None;
+    }
+    result.push_str(&text[cursor..]);
+    Some(result)
+}
+
+// Drops the first listed prefix the transcript starts with (case-insensitive, whole
+// words only) plus the punctuation and spaces after it: "Computer, open it" -> "open it".
+// Returns the rest and the prefix that matched.
+pub fn strip_prefix<'a>(text: &str, prefixes: &'a [String]) -> Option<(String, &'a str)> {
+    let text = text.trim_start();
+    prefixes.iter().find_map(|prefix| {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            return None;
+        }
+        let mut chars = text.char_indices();
+        let mut end = 0;
+        for p in prefix.chars() {
+            match chars.next() {
+                Some((i, c)) if chars_eq_ignore_case(c, p) => end = i + c.len_utf8(),
+                _ => return None,
+            }
+        }
+        let rest = &text[end..];
+        if rest.chars().next().is_some_and(char::is_alphanumeric) {
+            return None;
+        }
+        let rest = rest.trim_start_matches(|c: char| {
+            c.is_whitespace() || matches!(c, ',' | '.' | ':' | ';' | '!' | '?' | '-')
+        });
+        Some((rest.to_string(), prefix))
+    })
+}
+
+fn find_case_insensitive(haystack: &str, needle: &[char], from: usize) -> Option<(usize, usize)> {
+    for (offset, _) in haystack[from..].char_indices() {
+        let start = from + offset;
+        let mut chars = haystack[start..].char_indices();
+        let mut end = start;
+        let matched = needle.iter().all(|n| match chars.next() {
+            Some((i, c)) if chars_eq_ignore_case(c, *n) => {
+                end = start + i + c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+// Uppercases the first word and any word after `.`, `!` or `?`. Words that already
+// contain an uppercase letter (acronyms, "iPhone") are left alone, and abbreviations
+// with inner dots ("e.g.") don't end a sentence. Nothing is ever lowercased.
+pub fn auto_capitalize(text: &str, add_period: bool) -> String {
+    let mut out = String::with_capacity(text.len() + 1);
+    let mut sentence_start = true;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let trimmed = word.trim_end();
+        if sentence_start && !trimmed.chars().any(char::is_uppercase) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(chars.as_str());
+            }
+        } else {
+            out.push_str(word);
+        }
+        if !trimmed.is_empty() {
+            sentence_start = ends_sentence(trimmed);
+        }
+    }
+    if add_period
+        && out
+            .trim_end()
+            .chars()
+            .last()
+            .is_some_and(char::is_alphanumeric)
+    {
+        let trailing = out.len() - out.trim_end().len();
+        out.insert(out.len() - trailing, '.');
+    }
+    out
+}
+
+fn ends_sentence(word: &str) -> bool {
+    let core = word.trim_end_matches(['"', '\'', ')', '\u{201d}']);
+    match core.chars().last() {
+        Some('!' | '?') => true,
+        Some('.') => !core[..core.len() - 1].contains('.'),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(find: &str, replace: &str, regex: bool) -> ReplacementRule {
+        ReplacementRule {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            regex,
+        }
+    }
+
+    #[test]
+    fn regex_rules_use_groups_and_literal_rules_stay_literal() {
+        let mut replacements = Replacements::default();
+        let errors = replacements.update(&[
+            rule(r"(\d+) percent", "$1%", true),
+            rule("a.b", "dot", false),
+        ]);
+        assert!(errors.is_empty());
+        let (out, fired) = replacements.apply("50 percent of A.B and axb");
+        assert_eq!(out, "50% of dot and axb");
+        assert_eq!(fired.len(), 2);
+    }
+
+    #[test]
+    fn invalid_patterns_are_reported_once_and_skipped() {
+        let rules = [rule("(unclosed", "x", true), rule("foo", "bar", false)];
+        let mut replacements = Replacements::default();
+        let errors = replacements.update(&rules);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("(unclosed"));
+        assert!(replacements.update(&rules).is_empty());
+        let (out, _) = replacements.apply("(unclosed foo");
+        assert_eq!(out, "(unclosed bar");
+    }
+}
@@ -0,0 +1,148 @@
+// Windows notification-area icon: Show/Hide, a TTS bridge toggle and Quit.
+// A hidden eframe window gets no frames, so menu clicks are handled in tray-icon's
+// callback instead of `update`; only Quit goes through the app, after showing it.
+use std::sync::{Arc, Mutex};
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use tray_icon::{
+    Icon, MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent,
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    IsWindowVisible, SW_HIDE, SW_SHOW, SetForegroundWindow, ShowWindow,
+};
+
+use crate::{
+    app::AppEvent,
+    config::{self, AppConfig},
+    server::ServerControl,
+};
+
+pub struct Tray {
+    _icon: TrayIcon,
+    bridge_item: CheckMenuItem,
+    hwnd: isize,
+    quit_rx: Receiver<()>,
+}
+
+impl Tray {
+    pub fn new(
+        cc: &eframe::CreationContext,
+        config: Arc<Mutex<AppConfig>>,
+        server_control: ServerControl,
+        events: Sender<AppEvent>,
+    ) -> anyhow::Result<Self> {
+        let RawWindowHandle::Win32(handle) = cc.window_handle()?.as_raw() else {
+            return Err(anyhow::anyhow!("not a Win32 window"));
+        };
+        let hwnd = handle.hwnd.get();
+
+        let show_item = MenuItem::new("Show/Hide", true, None);
+        let bridge_item = CheckMenuItem::new(
+            "TTS bridge",
+            true,
+            config::lock(&config).tts_bridge_enabled,
+            None,
+        );
+        let quit_item = MenuItem::new("Quit", true, None);
+        let menu = Menu::with_items(&[
+            &show_item,
+            &bridge_item,
+            &PredefinedMenuItem::separator(),
+            &quit_item,
+        ])?;
+        let icon = crate::load_window_icon()
+            .ok_or_else(|| anyhow::anyhow!("app icon failed to decode"))?;
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("Push2Type")
+            .with_icon(Icon::from_rgba(icon.rgba, icon.width, icon.height)?)
+            .build()?;
+
+        let (quit_tx, quit_rx) = unbounded();
+        let ctx = cc.egui_ctx.clone();
+        let (show_id, bridge_id, quit_id) = (
+            show_item.id().clone(),
+            bridge_item.id().clone(),
+            quit_item.id().clone(),
+        );
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if event.id == show_id {
+                toggle_window(hwnd);
+            } else if event.id == bridge_id {
+                toggle_bridge(&config, &server_control, &events);
+            } else if event.id == quit_id {
+                // Shown first so the app gets the frame that runs its shutdown.
+                show_window(hwnd);
+                let _ = quit_tx.send(());
+                ctx.request_repaint();
+            }
+        }));
+        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                toggle_window(hwnd);
+            }
+        }));
+
+        Ok(Self {
+            _icon: icon,
+            bridge_item,
+            hwnd,
+            quit_rx,
+        })
+    }
+
+    pub fn hide_window(&self) {
+        unsafe { ShowWindow(self.hwnd as _, SW_HIDE) };
+    }
+
+    // The menu checks itself on click; this catches changes made in the window or over HTTP.
+    pub fn sync_bridge(&self, enabled: bool) {
+        if self.bridge_item.is_checked() != enabled {
+            self.bridge_item.set_checked(enabled);
+        }
+    }
+
+    pub fn quit_requested(&self) -> bool {
+        self.quit_rx.try_iter().count() > 0
+    }
+}
+
+fn show_window(hwnd: isize) {
+    unsafe {
+        ShowWindow(hwnd as _, SW_SHOW);
+        SetForegroundWindow(hwnd as _);
+    }
+}
+
+fn toggle_window(hwnd: isize) {
+    if unsafe { IsWindowVisible(hwnd as _) } != 0 {
+        unsafe { ShowWindow(hwnd as _, SW_HIDE) };
+    } else {
+        show_window(hwnd);
+    }
+}
+
+// Same effect as the window's bridge checkbox, but must work while the window is hidden.
+fn toggle_bridge(
+    config: &Arc<Mutex<AppConfig>>,
+    server_control: &ServerControl,
+    events: &Sender<AppEvent>,
+) {
+    let (enabled, saved) = {
+        let mut cfg = config::lock(config);
+        cfg.tts_bridge_enabled = !cfg.tts_bridge_enabled;
+        (cfg.tts_bridge_enabled, cfg.save())
+    };
+    if let Err(e) = saved {
+        let _ = events.send(AppEvent::Error(format!("config save failed: {e}")));
+    }
+    server_control.set_enabled(enabled);
+    let _ = events.send(AppEvent::ConfigChanged);
+}
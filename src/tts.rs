@@ -1,4 +1,5 @@
 use std::{
+    cell::Cell,
     net::TcpStream,
     sync::{Arc, Mutex},
     thread,
@@ -8,15 +9,32 @@ use std::{
 use base64::Engine;
 use crossbeam_channel::{Receiver, Sender};
 use reqwest::blocking::Client;
-use rodio::{OutputStream, Sink, buffer::SamplesBuffer};
+use rodio::{
+    DeviceTrait, OutputStream, Sink,
+    buffer::SamplesBuffer,
+    cpal::{self, traits::HostTrait},
+};
 use serde::{Deserialize, Serialize};
 use tungstenite::{Message, client::IntoClientRequest, connect, stream::MaybeTlsStream};
 
 use crate::{
     app::AppEvent,
-    config::{AppConfig, Provider},
+    config::{self, AppConfig, ListenDuck, Provider, TtsOverflowPolicy},
+    dsp,
+    http::{self, TimedClient},
+    supervisor,
 };
 
+const XAI_IDLE_TIMEOUT: Duration = Duration::from_secs(20);
+const XAI_RECONNECT_DELAY: Duration = Duration::from_millis(250);
+const VERBATIM_INSTRUCTIONS: &str =
+    "Speak exactly the most recent user message verbatim. No acknowledgements. No added words.";
+// xAI realtime, OpenAI `pcm` and ElevenLabs `pcm_24000` are all 24 kHz mono s16le.
+// Groq only returns WAV, whose header carries its own rate.
+const PCM_SAMPLE_RATE: u32 = 24_000;
+// Sink volume while ducked for a capture.
+const DUCK_VOLUME: f32 = 0.15;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeakRequest {
     pub message: String,
@@ -30,69 +48,436 @@ pub struct SpeakRequest {
 #[derive(Debug, Clone)]
 pub struct TtsRequest {
     pub speak: SpeakRequest,
+    // Optional per-request feedback for callers that want to follow playback.
+    pub progress: Option<Sender<TtsProgress>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TtsProgress {
+    Started { chunks: usize },
+    Chunk { index: usize, total: usize },
+    ChunkFailed { index: usize, error: String },
+    Done { played: usize, total: usize },
+    // The request itself was unusable (empty, missing key, wrong voice); nothing played.
+    Rejected(String),
+    Failed(String),
+}
+
+// The TTS worker owns the sink; hotkey capture holds it quiet while the mic is open.
+#[derive(Clone, Default)]
+pub struct PlaybackInterlock {
+    inner: Arc<Mutex<Interlock>>,
+}
+
+#[derive(Default)]
+struct Interlock {
+    sink: Option<Arc<Sink>>,
+    held: ListenDuck,
+}
+
+impl PlaybackInterlock {
+    pub fn hold(&self, mode: ListenDuck) {
+        self.update(|state| state.held = mode);
+    }
+
+    pub fn release(&self) {
+        self.update(|state| state.held = ListenDuck::Off);
+    }
+
+    // A sink opened mid-capture starts out ducked or paused too.
+    fn attach(&self, sink: Arc<Sink>) {
+        self.update(|state| state.sink = Some(sink));
+    }
+
+    fn detach(&self) {
+        if let Ok(mut state) = self.inner.lock() {
+            state.sink = None;
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Interlock)) {
+        let Ok(mut state) = self.inner.lock() else {
+            return;
+        };
+        change(&mut state);
+        if let Some(sink) = &state.sink {
+            match state.held {
+                ListenDuck::Off => {
+                    sink.set_volume(1.0);
+                    sink.play();
+                }
+                ListenDuck::Duck => {
+                    sink.set_volume(DUCK_VOLUME);
+                    sink.play();
+                }
+                ListenDuck::Pause => sink.pause(),
+            }
+        }
+    }
 }
 
 pub fn spawn_tts_worker(
     config: Arc<Mutex<AppConfig>>,
     events: Sender<AppEvent>,
     tts_rx: Receiver<TtsRequest>,
+    interlock: PlaybackInterlock,
 ) {
-    thread::spawn(move || {
-        let http = Client::new();
+    supervisor::spawn("tts", events.clone(), config.clone(), move || {
+        // Clears a busy flag left behind if the previous run crashed mid-request.
+        let _ = events.send(AppEvent::TtsBusy(false));
+        let mut http = TimedClient::new(config::lock(&config).http_timeout_secs);
+        // rodio's OutputStream isn't Send, so a kept-open output lives on this thread only.
+        let mut kept: Option<StreamingOutput> = None;
         while let Ok(req) = tts_rx.recv() {
             let _ = events.send(AppEvent::TtsBusy(true));
-            let current = config.lock().expect("config lock").clone();
-            let message = req.speak.message.trim().to_string();
+            let notify = |progress: TtsProgress| {
+                if let Some(tx) = &req.progress {
+                    let _ = tx.send(progress);
+                }
+            };
+            let current = config::lock(&config).clone();
+            let mut message = req.speak.message.trim().to_string();
             if message.is_empty() {
+                notify(TtsProgress::Rejected("empty speak message".to_string()));
                 let _ = events.send(AppEvent::Warning("empty speak message".to_string()));
                 let _ = events.send(AppEvent::TtsBusy(false));
                 continue;
             }
+            match fit_max_chars(&current, &message) {
+                Ok(fitted) if fitted.len() < message.len() => {
+                    let _ = events.send(AppEvent::Warning(format!(
+                        "tts message truncated from {} to {} chars (tts_max_chars)",
+                        message.chars().count(),
+                        fitted.chars().count()
+                    )));
+                    message = fitted;
+                }
+                Ok(_) => {}
+                Err(reason) => {
+                    notify(TtsProgress::Rejected(reason.clone()));
+                    let _ = events.send(AppEvent::Error(format!("tts request rejected: {reason}")));
+                    let _ = events.send(AppEvent::TtsBusy(false));
+                    continue;
+                }
+            }
 
-            let show_text = req.speak.show_text.unwrap_or(current.show_endpoint_text);
+            let speak = apply_voice_alias(&current, &req.speak);
+            let provider = speak.provider.unwrap_or(current.tts_provider);
+            if let Err(reason) = validate_request(&current, &speak, provider) {
+                let reason = format!(
+                    "tts request rejected (provider: {}): {reason}",
+                    provider_name(provider)
+                );
+                notify(TtsProgress::Rejected(reason.clone()));
+                let _ = events.send(AppEvent::Error(reason));
+                let _ = events.send(AppEvent::TtsBusy(false));
+                continue;
+            }
+
+            let show_text = speak.show_text.unwrap_or(current.show_endpoint_text);
             if show_text {
                 let _ = events.send(AppEvent::LastSpoken(message.clone()));
             }
 
-            let provider = req.speak.provider.unwrap_or(current.tts_provider);
-            let voice = resolve_voice(&current, &req.speak, provider);
-
-            let result = synthesize_with_provider(
-                &http,
-                &current,
-                &message,
-                &voice,
-                &req.speak
-                    .style
-                    .clone()
-                    .unwrap_or(current.xai_tts_style.clone()),
-                provider,
-            );
+            let voice = resolve_voice(&current, &speak, provider);
+            let style = resolve_style(&current, &speak);
+            let client = http.get(current.http_timeout_secs);
 
-            match result {
-                Ok(pcm) => {
-                    let _ = events.send(AppEvent::Info(format!(
-                        "tts provider used: {} voice: {}",
-                        provider_name(provider),
-                        voice
-                    )));
-                    if let Err(e) = play_pcm_24k_mono(&pcm) {
-                        let _ = events.send(AppEvent::Error(format!("audio playback failed: {e}")));
+            if kept
+                .as_ref()
+                .is_some_and(|output| !output.is_default_device())
+            {
+                let _ = events.send(AppEvent::Info(
+                    "audio output device changed; reopening".to_string(),
+                ));
+                kept = None;
+            }
+            if kept.is_none() {
+                match StreamingOutput::open(&interlock) {
+                    Ok(output) => kept = Some(output),
+                    Err(e) => {
+                        notify(TtsProgress::Failed(format!(
+                            "audio output unavailable: {e}"
+                        )));
+                        let _ =
+                            events.send(AppEvent::Error(format!("audio output unavailable: {e}")));
+                        let _ = events.send(AppEvent::TtsBusy(false));
+                        continue;
                     }
                 }
-                Err(e) => {
-                    let _ = events.send(AppEvent::Error(format!("tts failed: {e}")));
+            }
+            let output = kept.as_ref().expect("output opened above");
+            let chunks = split_into_chunks(&message, chunk_limit(&current, provider));
+            let total = chunks.len();
+            notify(TtsProgress::Started { chunks: total });
+            let mut played = 0;
+            let mut synthesis = Duration::ZERO;
+            for (index, chunk) in chunks.iter().enumerate() {
+                // Chunks queue on one sink, so the next one synthesizes while this one plays.
+                let synth_started = Instant::now();
+                let result = if provider == Provider::Xai && !current.mock_mode {
+                    xai_realtime_stream(&current, chunk, &voice, &style, output)
+                } else {
+                    synthesize_with_provider(client, &current, chunk, &voice, &style, provider).map(
+                        |(mut pcm, sample_rate)| {
+                            if let Some(mut normalizer) = Normalizer::new(&current) {
+                                normalizer.apply(&mut pcm);
+                            }
+                            output.play_pcm_mono(pcm, sample_rate)
+                        },
+                    )
+                };
+                synthesis += synth_started.elapsed();
+                match result {
+                    Ok(()) => {
+                        played += 1;
+                        notify(TtsProgress::Chunk {
+                            index: index + 1,
+                            total,
+                        });
+                    }
+                    Err(e) => {
+                        let error = http::describe(&e, current.http_timeout_secs);
+                        notify(TtsProgress::ChunkFailed {
+                            index: index + 1,
+                            error: error.clone(),
+                        });
+                        let _ = events.send(AppEvent::Error(format!(
+                            "tts failed on chunk {}/{total}: {error}",
+                            index + 1
+                        )));
+                        // The remaining chunks would wait out the same stall; give up on this request.
+                        if http::is_timeout(&e) {
+                            break;
+                        }
+                    }
                 }
             }
+            output.sink.sleep_until_end();
+            if played > 0 {
+                let _ = events.send(AppEvent::Metric {
+                    stage: "tts synthesis",
+                    ms: synthesis.as_millis() as u64,
+                });
+            }
+            if let Some(started) = output.first_audio.take() {
+                let _ = events.send(AppEvent::Metric {
+                    stage: "tts playback",
+                    ms: started.elapsed().as_millis() as u64,
+                });
+            }
+            if !current.tts_keep_output_open {
+                kept = None;
+            }
+            notify(TtsProgress::Done { played, total });
+            if played > 0 {
+                let _ = events.send(AppEvent::Info(format!(
+                    "tts provider used: {} voice: {} ({played}/{total} chunks)",
+                    provider_name(provider),
+                    voice
+                )));
+            }
 
             let _ = events.send(AppEvent::TtsBusy(false));
         }
     });
 }
 
+const GROQ_MAX_INPUT_CHARS: usize = 200;
+
+// Peaks below this are treated as silence and left alone.
+const NORMALIZE_SILENCE_PEAK: f32 = 64.0;
+// Caps how far a quiet voice is pushed up so room noise isn't blown up with it.
+const NORMALIZE_MAX_GAIN: f32 = 4.0;
+
+// Peak normalizer for one chunk of speech. Streamed audio only ever sees a
+// prefix of the chunk, so the gain follows the running peak and can only drop,
+// never pump back up mid-sentence.
+struct Normalizer {
+    target: f32,
+    peak: f32,
+}
+
+impl Normalizer {
+    fn new(cfg: &AppConfig) -> Option<Self> {
+        cfg.tts_normalize.then(|| Self {
+            target: i16::MAX as f32 * 10f32.powf(cfg.tts_target_peak.min(0.0) / 20.0),
+            peak: 0.0,
+        })
+    }
+
+    fn apply(&mut self, samples: &mut [i16]) {
+        let peak = samples
+            .iter()
+            .map(|s| s.unsigned_abs() as f32)
+            .fold(0.0, f32::max);
+        self.peak = self.peak.max(peak);
+        if self.peak < NORMALIZE_SILENCE_PEAK {
+            return;
+        }
+        let gain = (self.target / self.peak).min(NORMALIZE_MAX_GAIN);
+        for sample in samples.iter_mut() {
+            *sample = (*sample as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+}
+
+fn chunk_limit(cfg: &AppConfig, provider: Provider) -> usize {
+    let limit = cfg.tts_chunk_chars.max(20);
+    if provider == Provider::Groq {
+        limit.min(GROQ_MAX_INPUT_CHARS)
+    } else {
+        limit
+    }
+}
+
+// Applies `tts_max_chars` (0 = unlimited): over the limit, either an error or the
+// message cut back to whole sentences, or to a word boundary if even the first
+// sentence is too long.
+pub fn fit_max_chars(cfg: &AppConfig, message: &str) -> Result<String, String> {
+    let max = cfg.tts_max_chars;
+    let length = message.chars().count();
+    if max == 0 || length <= max {
+        return Ok(message.to_string());
+    }
+    if cfg.tts_overflow_policy == TtsOverflowPolicy::Reject {
+        return Err(format!(
+            "message is {length} chars, over the {max}-char limit (tts_max_chars)"
+        ));
+    }
+    let mut kept = String::new();
+    for sentence in split_sentences(message) {
+        let separator = usize::from(!kept.is_empty());
+        if kept.chars().count() + separator + sentence.chars().count() > max {
+            break;
+        }
+        if !kept.is_empty() {
+            kept.push(' ');
+        }
+        kept.push_str(&sentence);
+    }
+    if !kept.is_empty() {
+        return Ok(kept);
+    }
+    let head: String = message.chars().take(max).collect();
+    match head.rfind(char::is_whitespace) {
+        Some(end) if end > 0 => Ok(head[..end].trim_end().to_string()),
+        _ => Ok(head),
+    }
+}
+
+// Packs whole sentences into chunks of at most `max_chars`; sentences that are
+// still too long are split on word boundaries, and single huge words by chars.
+fn split_into_chunks(message: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    for sentence in split_sentences(message) {
+        if sentence.chars().count() <= max_chars {
+            pieces.push(sentence);
+            continue;
+        }
+        let mut current = String::new();
+        for word in sentence.split_whitespace() {
+            let word_len = word.chars().count();
+            if word_len > max_chars {
+                if !current.is_empty() {
+                    pieces.push(std::mem::take(&mut current));
+                }
+                let chars: Vec<char> = word.chars().collect();
+                pieces.extend(chars.chunks(max_chars).map(|c| c.iter().collect()));
+                continue;
+            }
+            let separator = usize::from(!current.is_empty());
+            if current.chars().count() + separator + word_len > max_chars {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        if !current.is_empty() {
+            pieces.push(current);
+        }
+    }
+
+    let mut chunks: Vec<String> = Vec::new();
+    for piece in pieces {
+        match chunks.last_mut() {
+            Some(last) if last.chars().count() + 1 + piece.chars().count() <= max_chars => {
+                last.push(' ');
+                last.push_str(&piece);
+            }
+            _ => chunks.push(piece),
+        }
+    }
+    chunks
+}
+
+fn split_sentences(message: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\n' {
+            push_trimmed(&mut sentences, &mut current);
+            continue;
+        }
+        current.push(c);
+        let ends_sentence = matches!(c, '.' | '!' | '?' | '\u{2026}')
+            && chars.peek().is_none_or(|next| next.is_whitespace());
+        if ends_sentence {
+            push_trimmed(&mut sentences, &mut current);
+        }
+    }
+    push_trimmed(&mut sentences, &mut current);
+    sentences
+}
+
+fn push_trimmed(out: &mut Vec<String>, current: &mut String) {
+    let trimmed = current.trim();
+    if !trimmed.is_empty() {
+        out.push(trimmed.to_string());
+    }
+    current.clear();
+}
+
+// Configuration problems the caller can act on are reported once, up front,
+// rather than as a synthesis failure on every chunk.
+fn validate_request(cfg: &AppConfig, req: &SpeakRequest, provider: Provider) -> Result<(), String> {
+    if !cfg.mock_mode && !config::has_api_key(provider) {
+        return Err(format!("{} is not set", config::api_key_env(provider)));
+    }
+    if let Some(voice) = &req.voice
+        && !is_valid_voice(provider, &normalize_voice(provider, voice))
+    {
+        return Err(format!(
+            "voice '{voice}' is not available for {}",
+            provider_name(provider)
+        ));
+    }
+    Ok(())
+}
+
+// A voice naming one of `voice_aliases` (case-insensitive) becomes the alias's voice and
+// provider, overriding whichever provider the request or the defaults would have used.
+fn apply_voice_alias(cfg: &AppConfig, speak: &SpeakRequest) -> SpeakRequest {
+    let mut speak = speak.clone();
+    if let Some(voice) = &speak.voice
+        && let Some(alias) = cfg
+            .voice_aliases
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(voice.trim()))
+            .map(|(_, alias)| alias.clone())
+    {
+        speak.voice = Some(alias.voice);
+        speak.provider = Some(alias.provider);
+    }
+    speak
+}
+
 fn resolve_voice(cfg: &AppConfig, req: &SpeakRequest, provider: Provider) -> String {
     if let Some(v) = &req.voice {
-        let candidate = v.to_lowercase();
+        let candidate = normalize_voice(provider, v);
         return if is_valid_voice(provider, &candidate) {
             candidate
         } else {
@@ -102,7 +487,7 @@ fn resolve_voice(cfg: &AppConfig, req: &SpeakRequest, provider: Provider) -> Str
     if let Some(p) = &req.persona {
         let persona = p.to_lowercase();
         if let Some(mapped) = cfg.persona_voices.get(&persona) {
-            let candidate = mapped.to_lowercase();
+            let candidate = normalize_voice(provider, mapped);
             return if is_valid_voice(provider, &candidate) {
                 candidate
             } else {
@@ -113,6 +498,18 @@ fn resolve_voice(cfg: &AppConfig, req: &SpeakRequest, provider: Provider) -> Str
     provider_default_voice(cfg, provider)
 }
 
+// Explicit style wins, then the persona's mapped style, then the global xAI style.
+fn resolve_style(cfg: &AppConfig, req: &SpeakRequest) -> String {
+    if let Some(style) = &req.style {
+        return style.clone();
+    }
+    req.persona
+        .as_ref()
+        .and_then(|p| cfg.persona_styles.get(&p.to_lowercase()))
+        .cloned()
+        .unwrap_or_else(|| cfg.xai_tts_style.clone())
+}
+
 fn synthesize_with_provider(
     client: &Client,
     cfg: &AppConfig,
@@ -120,48 +517,113 @@ fn synthesize_with_provider(
     voice: &str,
     style: &str,
     provider: Provider,
-) -> anyhow::Result<Vec<i16>> {
+) -> anyhow::Result<(Vec<i16>, u32)> {
+    if cfg.mock_mode {
+        return Ok((mock_speech(message), PCM_SAMPLE_RATE));
+    }
     match provider {
         Provider::Xai => {
             let key =
                 std::env::var("XAI_API_KEY").map_err(|_| anyhow::anyhow!("XAI_API_KEY missing"))?;
-            xai_realtime_tts(message, voice, style, &cfg.xai_realtime_model, &key)
+            let mut pcm = Vec::new();
+            xai_realtime_tts(
+                message,
+                voice,
+                Delivery::new(cfg, style),
+                &realtime_url(cfg),
+                &key,
+                cfg.xai_reconnect_attempts,
+                &mut pcm,
+            )?;
+            Ok((pcm, PCM_SAMPLE_RATE))
         }
         Provider::OpenAi => {
             let key = std::env::var("OPENAI_API_KEY")
                 .map_err(|_| anyhow::anyhow!("OPENAI_API_KEY missing"))?;
             openai_tts(
                 client,
-                "https://api.openai.com/v1/audio/speech",
+                &speech_url(cfg, Provider::OpenAi),
                 message,
                 voice,
                 &cfg.openai_tts_model,
                 &key,
-                "pcm",
+                Provider::OpenAi,
             )
         }
         Provider::Groq => {
             let key = std::env::var("GROQ_API_KEY")
                 .map_err(|_| anyhow::anyhow!("GROQ_API_KEY missing"))?;
-            if message.chars().count() > 200 {
+            if message.chars().count() > GROQ_MAX_INPUT_CHARS {
                 return Err(anyhow::anyhow!(
-                    "Groq Orpheus input max is 200 chars; got {}",
+                    "Groq Orpheus input max is {GROQ_MAX_INPUT_CHARS} chars; got {}",
                     message.chars().count()
                 ));
             }
             openai_tts(
                 client,
-                "https://api.groq.com/openai/v1/audio/speech",
+                &speech_url(cfg, Provider::Groq),
                 message,
                 voice,
                 &cfg.groq_tts_model,
                 &key,
-                "wav",
+                Provider::Groq,
             )
         }
+        Provider::ElevenLabs => {
+            let key = std::env::var("ELEVENLABS_API_KEY")
+                .map_err(|_| anyhow::anyhow!("ELEVENLABS_API_KEY missing"))?;
+            let url = format!(
+                "{}/text-to-speech/{voice}?output_format=pcm_24000",
+                cfg.tts_base_url(&provider)
+            );
+            let pcm = elevenlabs_tts(client, &url, message, &cfg.elevenlabs_tts_model, &key)?;
+            Ok((pcm, PCM_SAMPLE_RATE))
+        }
     }
 }
 
+// A beep roughly as long as the message would take to say, so queueing and
+// playback behave about as they would with a real provider.
+fn mock_speech(message: &str) -> Vec<i16> {
+    let words = message.split_whitespace().count() as u32;
+    let duration_ms = (words * 250).clamp(200, 3000);
+    let mut pcm = dsp::sine_tone(660.0, PCM_SAMPLE_RATE, duration_ms, 0.2);
+    pcm.extend(std::iter::repeat_n(0, PCM_SAMPLE_RATE as usize / 10));
+    pcm
+}
+
+// OpenAI-style `/audio/speech`, under `tts_base_urls` when one is set.
+fn speech_url(cfg: &AppConfig, provider: Provider) -> String {
+    format!("{}/audio/speech", cfg.tts_base_url(&provider))
+}
+
+// Requests raw 24 kHz s16le so the audio matches the other providers' PCM.
+fn elevenlabs_tts(
+    client: &Client,
+    url: &str,
+    message: &str,
+    model: &str,
+    api_key: &str,
+) -> anyhow::Result<Vec<i16>> {
+    let body = serde_json::json!({
+        "text": message,
+        "model_id": model,
+    });
+    let response = client
+        .post(url)
+        .header("xi-api-key", api_key)
+        .json(&body)
+        .send()?;
+    if !response.status().is_success() {
+        return Err(http::provider_error(Provider::ElevenLabs, response));
+    }
+    let bytes = response.bytes()?;
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|c| i16::from_le_bytes([c[0], c[1]]))
+        .collect())
+}
+
 fn openai_tts(
     client: &Client,
     url: &str,
@@ -169,8 +631,14 @@ fn openai_tts(
     voice: &str,
     model: &str,
     api_key: &str,
-    response_format: &str,
-) -> anyhow::Result<Vec<i16>> {
+    provider: Provider,
+) -> anyhow::Result<(Vec<i16>, u32)> {
+    // Groq only offers WAV; OpenAI's raw PCM skips the header parse.
+    let response_format = if provider == Provider::Groq {
+        "wav"
+    } else {
+        "pcm"
+    };
     let body = serde_json::json!({
         "model": model,
         "voice": voice,
@@ -179,17 +647,22 @@ fn openai_tts(
     });
     let response = client.post(url).bearer_auth(api_key).json(&body).send()?;
     if !response.status().is_success() {
-        let status = response.status();
-        let body_text = response.text().unwrap_or_else(|_| "<no body>".to_string());
-        return Err(anyhow::anyhow!("HTTP {} body: {}", status, body_text));
+        return Err(http::provider_error(provider, response));
     }
     let bytes = response.bytes()?;
+    decode_speech(&bytes, response_format)
+}
+
+fn decode_speech(bytes: &[u8], response_format: &str) -> anyhow::Result<(Vec<i16>, u32)> {
     match response_format {
-        "pcm" => Ok(bytes
-            .chunks_exact(2)
-            .map(|c| i16::from_le_bytes([c[0], c[1]]))
-            .collect()),
-        "wav" => decode_wav_to_i16(bytes.as_ref()),
+        "pcm" => Ok((
+            bytes
+                .chunks_exact(2)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]))
+                .collect(),
+            PCM_SAMPLE_RATE,
+        )),
+        "wav" => dsp::decode_wav_to_i16(bytes),
         _ => Err(anyhow::anyhow!(
             "unsupported response_format decode path: {}",
             response_format
@@ -197,24 +670,169 @@ fn openai_tts(
     }
 }
 
-fn xai_realtime_tts(
+fn xai_realtime_stream(
+    cfg: &AppConfig,
     message: &str,
     voice: &str,
     style: &str,
-    model: &str,
+    output: &StreamingOutput,
+) -> anyhow::Result<()> {
+    let key = std::env::var("XAI_API_KEY").map_err(|_| anyhow::anyhow!("XAI_API_KEY missing"))?;
+    xai_realtime_tts(
+        message,
+        voice,
+        Delivery::new(cfg, style),
+        &realtime_url(cfg),
+        &key,
+        cfg.xai_reconnect_attempts,
+        &mut Speakers {
+            output,
+            normalizer: Normalizer::new(cfg),
+        },
+    )
+}
+
+// Where realtime audio goes. `deliver` reports whether the chunk reached the output
+// device; a chunk that only landed in a buffer can still be thrown away and redone.
+trait RealtimeSink {
+    fn deliver(&mut self, chunk: Vec<i16>) -> bool;
+    fn discard(&mut self);
+}
+
+// The buffered path: nothing is heard until synthesis finishes.
+impl RealtimeSink for Vec<i16> {
+    fn deliver(&mut self, chunk: Vec<i16>) -> bool {
+        self.extend_from_slice(&chunk);
+        false
+    }
+
+    fn discard(&mut self) {
+        self.clear();
+    }
+}
+
+struct Speakers<'a> {
+    output: &'a StreamingOutput,
+    normalizer: Option<Normalizer>,
+}
+
+impl RealtimeSink for Speakers<'_> {
+    fn deliver(&mut self, mut chunk: Vec<i16>) -> bool {
+        if chunk.is_empty() {
+            return false;
+        }
+        if let Some(normalizer) = self.normalizer.as_mut() {
+            normalizer.apply(&mut chunk);
+        }
+        self.output.play_pcm_mono(chunk, PCM_SAMPLE_RATE);
+        true
+    }
+
+    fn discard(&mut self) {}
+}
+
+// The websocket lives beside the REST API, so an https base maps onto wss.
+fn realtime_url(cfg: &AppConfig) -> String {
+    let base = cfg.tts_base_url(&Provider::Xai);
+    let base = match base.split_once("://") {
+        Some(("https", rest)) => format!("wss://{rest}"),
+        Some(("http", rest)) => format!("ws://{rest}"),
+        _ => base,
+    };
+    format!("{base}/realtime?model={}", cfg.xai_realtime_model)
+}
+
+// How the realtime model is told to speak: `style` always goes into the session, and
+// with `verbatim` off it also replaces the read-it-word-for-word response instruction.
+#[derive(Clone, Copy)]
+struct Delivery<'a> {
+    style: &'a str,
+    verbatim: bool,
+}
+
+impl<'a> Delivery<'a> {
+    fn new(cfg: &AppConfig, style: &'a str) -> Self {
+        Self {
+            style,
+            verbatim: cfg.tts_verbatim,
+        }
+    }
+
+    fn response_instructions(&self) -> &'a str {
+        if self.verbatim {
+            VERBATIM_INSTRUCTIONS
+        } else {
+            self.style
+        }
+    }
+}
+
+// The socket closed (or never opened) before `response.done`; worth another try.
+#[derive(Debug)]
+struct ConnectionDropped(String);
+
+impl std::fmt::Display for ConnectionDropped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "xAI realtime connection dropped: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConnectionDropped {}
+
+// Reconnects only while nothing has reached the speakers: a second generation never
+// lines up with the first, so retrying after audio was heard would repeat or garble
+// speech. Audio that was only buffered is discarded and generated again.
+fn xai_realtime_tts(
+    message: &str,
+    voice: &str,
+    delivery: Delivery,
+    url: &str,
     api_key: &str,
-) -> anyhow::Result<Vec<i16>> {
-    let mut request = format!("wss://api.x.ai/v1/realtime?model={model}").into_client_request()?;
+    reconnect_attempts: u32,
+    sink: &mut impl RealtimeSink,
+) -> anyhow::Result<()> {
+    let mut attempt = 0;
+    loop {
+        let mut heard = false;
+        let result = xai_realtime_once(message, voice, delivery, url, api_key, |chunk| {
+            heard |= sink.deliver(chunk);
+        });
+        match result {
+            Err(e)
+                if !heard
+                    && attempt < reconnect_attempts
+                    && e.downcast_ref::<ConnectionDropped>().is_some() =>
+            {
+                sink.discard();
+                attempt += 1;
+                thread::sleep(XAI_RECONNECT_DELAY * attempt);
+            }
+            result => return result,
+        }
+    }
+}
+
+fn xai_realtime_once(
+    message: &str,
+    voice: &str,
+    delivery: Delivery,
+    url: &str,
+    api_key: &str,
+    on_audio: impl FnMut(Vec<i16>),
+) -> anyhow::Result<()> {
+    let mut request = url.into_client_request()?;
     request.headers_mut().insert(
         "Authorization",
         format!("Bearer {api_key}")
             .parse()
             .map_err(|e| anyhow::anyhow!("{e}"))?,
     );
-    let (mut ws, _) = connect(request)?;
-    send_session_update(&mut ws, voice, style)?;
-    send_message_and_response(&mut ws, message)?;
-    read_audio_until_done(&mut ws)
+    let (mut ws, _) = connect(request).map_err(|e| ConnectionDropped(e.to_string()))?;
+    send_session_update(&mut ws, voice, delivery.style)
+        .map_err(|e| ConnectionDropped(e.to_string()))?;
+    send_message_and_response(&mut ws, message, delivery.response_instructions())
+        .map_err(|e| ConnectionDropped(e.to_string()))?;
+    read_audio_until_done(&mut ws, on_audio)
 }
 
 fn send_session_update(
@@ -232,7 +850,7 @@ fn send_session_update(
                 "output": {
                     "format": {
                         "type": "audio/pcm",
-                        "rate": 24000
+                        "rate": PCM_SAMPLE_RATE
                     }
                 }
             }
@@ -245,6 +863,7 @@ fn send_session_update(
 fn send_message_and_response(
     ws: &mut tungstenite::WebSocket<MaybeTlsStream<TcpStream>>,
     message: &str,
+    instructions: &str,
 ) -> anyhow::Result<()> {
     let item = serde_json::json!({
         "type": "conversation.item.create",
@@ -260,7 +879,7 @@ fn send_message_and_response(
         "type": "response.create",
         "response": {
             "modalities": ["audio"],
-            "instructions": "Speak exactly the most recent user message verbatim. No acknowledgements. No added words."
+            "instructions": instructions
         }
     });
     ws.send(Message::Text(response.to_string()))?;
@@ -269,59 +888,165 @@ fn send_message_and_response(
 
 fn read_audio_until_done(
     ws: &mut tungstenite::WebSocket<MaybeTlsStream<TcpStream>>,
-) -> anyhow::Result<Vec<i16>> {
-    let start = Instant::now();
-    let mut pcm_bytes = Vec::<u8>::new();
+    mut on_audio: impl FnMut(Vec<i16>),
+) -> anyhow::Result<()> {
+    let mut last_audio = Instant::now();
+    let mut audio = RealtimeAudio::default();
     loop {
-        if start.elapsed() > Duration::from_secs(20) {
-            return Err(anyhow::anyhow!("xAI realtime timed out"));
-        }
-        let msg = ws.read()?;
-        if let Message::Text(text) = msg {
-            let value: serde_json::Value = serde_json::from_str(&text)?;
-            let event_type = value
-                .get("type")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default();
-            if event_type == "response.output_audio.delta" || event_type == "response.audio.delta" {
-                if let Some(delta) = value.get("delta").and_then(|v| v.as_str()) {
-                    let chunk =
-                        base64::engine::general_purpose::STANDARD.decode(delta.as_bytes())?;
-                    pcm_bytes.extend_from_slice(&chunk);
-                }
-            }
-            if event_type == "response.output_item.done" {
-                if let Some(content) = value.pointer("/item/content").and_then(|v| v.as_array()) {
-                    for part in content {
-                        if let Some(audio) = part.get("audio").and_then(|v| v.as_str()) {
-                            let chunk = base64::engine::general_purpose::STANDARD
-                                .decode(audio.as_bytes())?;
-                            pcm_bytes.extend_from_slice(&chunk);
-                        }
-                    }
-                }
+        // Idle timeout: resets on every audio delta so long, actively streaming replies finish.
+        if last_audio.elapsed() > XAI_IDLE_TIMEOUT {
+            return Err(anyhow::anyhow!("xAI realtime timed out waiting for audio"));
+        }
+        let fed = match ws.read().map_err(|e| ConnectionDropped(e.to_string()))? {
+            Message::Text(text) => audio.feed_text(&text, &mut on_audio)?,
+            Message::Binary(bytes) => audio.feed_binary(&bytes, &mut on_audio),
+            Message::Close(frame) => {
+                let reason = frame.map_or_else(
+                    || "closed by server".to_string(),
+                    |frame| format!("closed by server ({}: {})", frame.code, frame.reason),
+                );
+                return Err(ConnectionDropped(reason).into());
             }
-            if event_type == "response.done" {
-                break;
+            // tungstenite queues the pong itself; flush so it goes out now, not on the next read.
+            Message::Ping(_) => {
+                ws.flush().map_err(|e| ConnectionDropped(e.to_string()))?;
+                continue;
             }
-            if event_type == "error" {
-                return Err(anyhow::anyhow!("xAI realtime returned error: {value}"));
+            Message::Pong(_) | Message::Frame(_) => continue,
+        };
+        match fed {
+            Fed::Audio => last_audio = Instant::now(),
+            Fed::Done => return Ok(()),
+            Fed::Nothing => {}
+        }
+    }
+}
+
+enum Fed {
+    Audio,
+    Done,
+    Nothing,
+}
+
+// Reassembles PCM16 from realtime frames: base64 deltas inside JSON text events, or
+// raw PCM in binary frames on the variants that send those. Odd trailing bytes wait
+// in `pending` for the next frame.
+#[derive(Default)]
+struct RealtimeAudio {
+    pending: Vec<u8>,
+    received_delta: bool,
+}
+
+impl RealtimeAudio {
+    fn feed_text(
+        &mut self,
+        text: &str,
+        on_audio: &mut impl FnMut(Vec<i16>),
+    ) -> anyhow::Result<Fed> {
+        let value: serde_json::Value = serde_json::from_str(text)?;
+        let event_type = value
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if (event_type == "response.output_audio.delta" || event_type == "response.audio.delta")
+            && let Some(delta) = value.get("delta").and_then(|v| v.as_str())
+        {
+            let chunk = base64::engine::general_purpose::STANDARD.decode(delta.as_bytes())?;
+            return Ok(self.feed_binary(&chunk, on_audio));
+        }
+        // Some responses only carry audio on the completed item; skip it when deltas already
+        // delivered the same audio so streamed playback doesn't repeat itself.
+        if event_type == "response.output_item.done"
+            && !self.received_delta
+            && let Some(content) = value.pointer("/item/content").and_then(|v| v.as_array())
+        {
+            for part in content {
+                if let Some(audio) = part.get("audio").and_then(|v| v.as_str()) {
+                    let chunk =
+                        base64::engine::general_purpose::STANDARD.decode(audio.as_bytes())?;
+                    self.pending.extend_from_slice(&chunk);
+                    emit_pcm16(&mut self.pending, on_audio);
+                }
             }
         }
+        if event_type == "response.done" {
+            return Ok(Fed::Done);
+        }
+        if event_type == "error" {
+            return Err(anyhow::anyhow!("xAI realtime returned error: {value}"));
+        }
+        Ok(Fed::Nothing)
+    }
+
+    fn feed_binary(&mut self, bytes: &[u8], on_audio: &mut impl FnMut(Vec<i16>)) -> Fed {
+        self.pending.extend_from_slice(bytes);
+        self.received_delta = true;
+        emit_pcm16(&mut self.pending, on_audio);
+        Fed::Audio
+    }
+}
+
+fn emit_pcm16(pending: &mut Vec<u8>, on_audio: &mut impl FnMut(Vec<i16>)) {
+    let usable = pending.len() - pending.len() % 2;
+    if usable == 0 {
+        return;
     }
-    Ok(pcm_bytes
+    let samples = pending[..usable]
         .chunks_exact(2)
         .map(|c| i16::from_le_bytes([c[0], c[1]]))
-        .collect())
+        .collect();
+    pending.drain(..usable);
+    on_audio(samples);
 }
 
-fn play_pcm_24k_mono(samples: &[i16]) -> anyhow::Result<()> {
-    let (_stream, handle) = OutputStream::try_default()?;
-    let sink = Sink::try_new(&handle)?;
-    let source = SamplesBuffer::new(1, 24_000, samples.to_vec());
-    sink.append(source);
-    sink.sleep_until_end();
-    Ok(())
+struct StreamingOutput {
+    _stream: OutputStream,
+    sink: Arc<Sink>,
+    interlock: PlaybackInterlock,
+    // Name of the device it was opened on, to notice a new default between requests.
+    device: Option<String>,
+    // When the current request's first samples were queued; playback runs from here.
+    first_audio: Cell<Option<Instant>>,
+}
+
+impl StreamingOutput {
+    fn open(interlock: &PlaybackInterlock) -> anyhow::Result<Self> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| anyhow::anyhow!("no default output device"))?;
+        let (stream, handle) = OutputStream::try_from_device(&device)?;
+        let sink = Arc::new(Sink::try_new(&handle)?);
+        interlock.attach(sink.clone());
+        Ok(Self {
+            _stream: stream,
+            sink,
+            interlock: interlock.clone(),
+            device: device.name().ok(),
+            first_audio: Cell::new(None),
+        })
+    }
+
+    // Queues behind whatever is already playing; rodio resamples to the device rate.
+    fn play_pcm_mono(&self, samples: Vec<i16>, sample_rate: u32) {
+        if self.first_audio.get().is_none() {
+            self.first_audio.set(Some(Instant::now()));
+        }
+        self.sink
+            .append(SamplesBuffer::new(1, sample_rate, samples));
+    }
+
+    fn is_default_device(&self) -> bool {
+        let current = cpal::default_host()
+            .default_output_device()
+            .and_then(|device| device.name().ok());
+        current.is_some() && current == self.device
+    }
+}
+
+impl Drop for StreamingOutput {
+    fn drop(&mut self) {
+        self.interlock.detach();
+    }
 }
 
 fn normalize_voice_name(raw: &str) -> String {
@@ -338,6 +1063,15 @@ fn provider_name(provider: Provider) -> &'static str {
         Provider::Xai => "xai",
         Provider::OpenAi => "openai",
         Provider::Groq => "groq",
+        Provider::ElevenLabs => "elevenlabs",
+    }
+}
+
+// ElevenLabs voice IDs are case-sensitive; every other provider uses lowercase names.
+pub fn normalize_voice(provider: Provider, voice: &str) -> String {
+    match provider {
+        Provider::ElevenLabs => voice.trim().to_string(),
+        _ => voice.trim().to_lowercase(),
     }
 }
 
@@ -346,10 +1080,11 @@ fn provider_default_voice(cfg: &AppConfig, provider: Provider) -> String {
         Provider::Xai => cfg.xai_voice.to_lowercase(),
         Provider::OpenAi => cfg.openai_voice.to_lowercase(),
         Provider::Groq => cfg.groq_voice.to_lowercase(),
+        Provider::ElevenLabs => cfg.elevenlabs_voice.trim().to_string(),
     }
 }
 
-fn is_valid_voice(provider: Provider, voice: &str) -> bool {
+pub fn is_valid_voice(provider: Provider, voice: &str) -> bool {
     match provider {
         Provider::OpenAi => matches!(
             voice,
@@ -372,123 +1107,172 @@ fn is_valid_voice(provider: Provider, voice: &str) -> bool {
             "autumn" | "diana" | "hannah" | "austin" | "daniel" | "troy"
         ),
         Provider::Xai => matches!(voice, "ara" | "rex" | "sal" | "eve" | "leo"),
+        Provider::ElevenLabs => {
+            !voice.is_empty() && voice.chars().all(|c| c.is_ascii_alphanumeric())
+        }
     }
 }
 
-fn decode_wav_to_i16(bytes: &[u8]) -> anyhow::Result<Vec<i16>> {
-    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
-        return Err(anyhow::anyhow!("invalid wav header"));
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VoiceAlias;
 
-    let mut offset = 12usize;
-    let mut audio_format: Option<u16> = None;
-    let mut channels: Option<u16> = None;
-    let mut bits_per_sample: Option<u16> = None;
-    let mut data_slice: Option<&[u8]> = None;
+    #[test]
+    fn max_chars_truncates_to_sentences_or_rejects() {
+        let mut cfg = AppConfig {
+            tts_max_chars: 30,
+            ..AppConfig::default()
+        };
+        let message = "First sentence here. Second one is longer than that.";
+        assert_eq!(
+            fit_max_chars(&cfg, message).unwrap(),
+            "First sentence here."
+        );
+        assert_eq!(
+            fit_max_chars(&cfg, "one enormous run-on clause without any stops at all").unwrap(),
+            "one enormous run-on clause"
+        );
+        assert_eq!(fit_max_chars(&cfg, "Short.").unwrap(), "Short.");
 
-    while offset + 8 <= bytes.len() {
-        let chunk_id = &bytes[offset..offset + 4];
-        let chunk_size = u32::from_le_bytes([
-            bytes[offset + 4],
-            bytes[offset + 5],
-            bytes[offset + 6],
-            bytes[offset + 7],
-        ]) as usize;
-        let chunk_start = offset + 8;
-        if chunk_start > bytes.len() {
-            break;
-        }
-        let chunk_end = (chunk_start + chunk_size).min(bytes.len());
-
-        if chunk_id == b"fmt " && chunk_end >= chunk_start + 16 {
-            audio_format = Some(u16::from_le_bytes([
-                bytes[chunk_start],
-                bytes[chunk_start + 1],
-            ]));
-            channels = Some(u16::from_le_bytes([
-                bytes[chunk_start + 2],
-                bytes[chunk_start + 3],
-            ]));
-            bits_per_sample = Some(u16::from_le_bytes([
-                bytes[chunk_start + 14],
-                bytes[chunk_start + 15],
-            ]));
-        } else if chunk_id == b"data" {
-            data_slice = Some(&bytes[chunk_start..chunk_end]);
-            break;
-        }
+        cfg.tts_overflow_policy = TtsOverflowPolicy::Reject;
+        assert!(fit_max_chars(&cfg, message).is_err());
+        cfg.tts_max_chars = 0;
+        assert_eq!(fit_max_chars(&cfg, message).unwrap(), message);
+    }
+
+    #[test]
+    fn voice_alias_overrides_provider_and_voice() {
+        let mut cfg = AppConfig::default();
+        cfg.voice_aliases.insert(
+            "Narrator".to_string(),
+            VoiceAlias {
+                provider: Provider::OpenAi,
+                voice: "onyx".to_string(),
+            },
+        );
+        let request = |voice: &str| SpeakRequest {
+            message: "hi".to_string(),
+            persona: None,
+            voice: Some(voice.to_string()),
+            provider: Some(Provider::Groq),
+            show_text: None,
+            style: None,
+        };
+        let resolved = apply_voice_alias(&cfg, &request("narrator"));
+        assert_eq!(resolved.voice.as_deref(), Some("onyx"));
+        assert_eq!(resolved.provider, Some(Provider::OpenAi));
 
-        offset = chunk_start + chunk_size + (chunk_size % 2);
+        let plain = apply_voice_alias(&cfg, &request("troy"));
+        assert_eq!(plain.voice.as_deref(), Some("troy"));
+        assert_eq!(plain.provider, Some(Provider::Groq));
     }
 
-    let fmt = audio_format.ok_or_else(|| anyhow::anyhow!("wav fmt chunk missing"))?;
-    let ch = channels.ok_or_else(|| anyhow::anyhow!("wav channels missing"))?;
-    let bps = bits_per_sample.ok_or_else(|| anyhow::anyhow!("wav bits_per_sample missing"))?;
-    let data = data_slice.ok_or_else(|| anyhow::anyhow!("wav data chunk missing"))?;
+    #[test]
+    fn realtime_audio_mixes_text_deltas_and_binary_frames() {
+        let b64 = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+        let delta = |bytes: &[u8]| {
+            serde_json::json!({ "type": "response.output_audio.delta", "delta": b64(bytes) })
+                .to_string()
+        };
+        let mut audio = RealtimeAudio::default();
+        let mut samples = Vec::new();
+        let mut collect = |chunk: Vec<i16>| samples.extend(chunk);
+
+        assert!(matches!(
+            audio.feed_text(&delta(&[0u8; 100]), &mut collect).unwrap(),
+            Fed::Audio
+        ));
+        // Odd-length binary frame: the last byte waits for the next frame.
+        assert!(matches!(
+            audio.feed_binary(&[1u8; 51], &mut collect),
+            Fed::Audio
+        ));
+        audio.feed_text(&delta(&[2u8; 1]), &mut collect).unwrap();
+        audio
+            .feed_text(
+                r#"{"type":"response.audio_transcript.delta"}"#,
+                &mut collect,
+            )
+            .unwrap();
+        assert!(matches!(
+            audio
+                .feed_text(r#"{"type":"response.done"}"#, &mut collect)
+                .unwrap(),
+            Fed::Done
+        ));
 
-    let sample_bytes = (bps / 8) as usize;
-    if sample_bytes == 0 {
-        return Err(anyhow::anyhow!("invalid wav sample size"));
+        // 100 + 51 + 1 bytes in, all paired up by the end.
+        assert_eq!(samples.len(), 76);
+        assert!(audio.pending.is_empty());
     }
-    let frame_bytes = sample_bytes * ch as usize;
-    if frame_bytes == 0 {
-        return Err(anyhow::anyhow!("invalid wav frame size"));
+
+    #[test]
+    fn realtime_audio_skips_item_audio_after_deltas() {
+        let mut audio = RealtimeAudio::default();
+        let mut samples = Vec::new();
+        let mut collect = |chunk: Vec<i16>| samples.extend(chunk);
+        audio.feed_binary(&[0u8; 8], &mut collect);
+        let item = serde_json::json!({
+            "type": "response.output_item.done",
+            "item": { "content": [{ "audio": base64::engine::general_purpose::STANDARD.encode([0u8; 8]) }] }
+        });
+        audio.feed_text(&item.to_string(), &mut collect).unwrap();
+        assert_eq!(samples.len(), 4);
     }
-    let valid_len = (data.len() / frame_bytes) * frame_bytes;
-    let payload = &data[..valid_len];
 
-    let mut out = Vec::with_capacity(payload.len() / frame_bytes);
-    for frame in payload.chunks_exact(frame_bytes) {
-        let mut sum = 0i32;
-        for channel_idx in 0..ch as usize {
-            let start = channel_idx * sample_bytes;
-            let s = decode_wav_sample_to_i16(fmt, bps, &frame[start..start + sample_bytes])?;
-            sum += s as i32;
-        }
-        out.push((sum / ch as i32) as i16);
+    #[test]
+    fn speech_decode_keeps_the_wav_sample_rate() {
+        let samples = vec![0i16, 1000, -1000, i16::MAX];
+        let wav = dsp::pcm_to_wav_bytes(&samples, 48_000).unwrap();
+        assert_eq!(decode_speech(&wav, "wav").unwrap(), (samples, 48_000));
+
+        let pcm = [0x10u8, 0x00, 0xF0, 0xFF];
+        assert_eq!(
+            decode_speech(&pcm, "pcm").unwrap(),
+            (vec![16, -16], PCM_SAMPLE_RATE)
+        );
     }
-    Ok(out)
-}
 
-fn decode_wav_sample_to_i16(
-    format: u16,
-    bits_per_sample: u16,
-    bytes: &[u8],
-) -> anyhow::Result<i16> {
-    match (format, bits_per_sample) {
-        (1, 8) => {
-            let v = bytes[0] as i32 - 128;
-            Ok((v << 8) as i16)
-        }
-        (1, 16) => Ok(i16::from_le_bytes([bytes[0], bytes[1]])),
-        (1, 24) => {
-            let b0 = bytes[0] as i32;
-            let b1 = (bytes[1] as i32) << 8;
-            let b2 = (bytes[2] as i32) << 16;
-            let mut v = b0 | b1 | b2;
-            if (v & 0x800000) != 0 {
-                v |= !0x00FF_FFFF;
+    #[test]
+    fn buffered_realtime_audio_is_discarded_when_the_connection_drops() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            for fill in [1u8, 2] {
+                let (stream, _) = listener.accept().unwrap();
+                let mut ws = tungstenite::accept(stream).unwrap();
+                // session.update, conversation.item.create, response.create
+                for _ in 0..3 {
+                    ws.read().unwrap();
+                }
+                let delta = serde_json::json!({
+                    "type": "response.output_audio.delta",
+                    "delta": base64::engine::general_purpose::STANDARD.encode([fill; 4]),
+                });
+                ws.send(Message::Text(delta.to_string())).unwrap();
+                if fill == 1 {
+                    ws.close(None).unwrap();
+                    ws.flush().unwrap();
+                } else {
+                    ws.send(Message::Text(r#"{"type":"response.done"}"#.into()))
+                        .unwrap();
+                }
             }
-            Ok((v >> 8) as i16)
-        }
-        (1, 32) => {
-            let v = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-            Ok((v >> 16) as i16)
-        }
-        (3, 32) => {
-            let f = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-            Ok((f.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16)
-        }
-        (3, 64) => {
-            let f = f64::from_le_bytes([
-                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-            ]);
-            Ok((f.clamp(-1.0, 1.0) * i16::MAX as f64).round() as i16)
-        }
-        _ => Err(anyhow::anyhow!(
-            "unsupported wav format code {} / {} bits",
-            format,
-            bits_per_sample
-        )),
+        });
+        let cfg = AppConfig::default();
+        let mut pcm = Vec::new();
+        xai_realtime_tts(
+            "hi",
+            "rex",
+            Delivery::new(&cfg, ""),
+            &url,
+            "key",
+            1,
+            &mut pcm,
+        )
+        .unwrap();
+        server.join().unwrap();
+        assert_eq!(pcm, vec![i16::from_le_bytes([2, 2]); 2]);
     }
 }